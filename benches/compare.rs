@@ -1,3 +1,4 @@
+use Rhai_Learning::host::HostEngine;
 use criterion::{Criterion, criterion_group, criterion_main};
 use rhai::{AST, Engine};
 
@@ -12,11 +13,12 @@ fn rhai_sum(engine: &Engine, ast: &AST) -> i64 {
 }
 
 fn benchmark(c: &mut Criterion) {
-    let engine = Engine::new();
+    let host = HostEngine::builder().build();
+    let engine = host.engine();
     let ast = engine.compile(SCRIPT).expect("compile script");
 
-    c.bench_function("pure_rust_sum", |b| b.iter(|| pure_rust_sum()));
-    c.bench_function("rhai_script_sum", |b| b.iter(|| rhai_sum(&engine, &ast)));
+    c.bench_function("pure_rust_sum", |b| b.iter(pure_rust_sum));
+    c.bench_function("rhai_script_sum", |b| b.iter(|| rhai_sum(engine, &ast)));
 }
 
 criterion_group!(benches, benchmark);
@@ -0,0 +1,308 @@
+//! Headless command-line interface for running examples without a display.
+
+use crate::examples::{Example, ExampleRegistry, ManifestProblem};
+use Rhai_Learning::workspace::Workspace;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: Rhai_Learning [--root <DIR>] [COMMAND]
+
+Without a command the graphical interface is started.
+
+Options:
+  --root <DIR>  Workspace holding examples/ and logs/. Defaults to the first of
+                $RHAI_LEARNING_ROOT, the `root` key of
+                $XDG_CONFIG_HOME/rhai-learning/config.toml (or
+                ~/.config/rhai-learning/config.toml), the current directory
+                if it holds examples/manifest.toml, and the directory the
+                program was built from
+
+Commands:
+  list        List the available examples
+  run <id>    Run an example and print its output and result
+  check       Compile every example script without running it
+  test        Check every example against its expectations and run its test functions
+  help        Show this message";
+
+/// Remove a `--root <DIR>` or `--root=<DIR>` option from `args`, returning its value.
+pub fn take_root(args: &mut Vec<String>) -> Result<Option<PathBuf>, String> {
+    let Some(i) = args
+        .iter()
+        .position(|a| a == "--root" || a.starts_with("--root="))
+    else {
+        return Ok(None);
+    };
+    let arg = args.remove(i);
+    let root = match arg.strip_prefix("--root=") {
+        Some(root) => root.to_string(),
+        None if i < args.len() => args.remove(i),
+        None => return Err("`--root` expects a directory".to_string()),
+    };
+    if root.is_empty() {
+        return Err("`--root` expects a directory".to_string());
+    }
+    Ok(Some(root.into()))
+}
+
+/// A command given on the command line.
+pub enum Command {
+    List,
+    Run(String),
+    Check,
+    Test,
+    Help,
+}
+
+impl Command {
+    /// Parse the arguments following the program name.
+    ///
+    /// Returns `Ok(None)` when no command is given and the GUI should start.
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let Some((command, rest)) = args.split_first() else {
+            return Ok(None);
+        };
+        let command = match (command.as_str(), rest) {
+            ("list", []) => Self::List,
+            ("run", [id]) => Self::Run(id.clone()),
+            ("run", _) => return Err("`run` expects exactly one example id".to_string()),
+            ("check", []) => Self::Check,
+            ("test", []) => Self::Test,
+            ("help" | "--help" | "-h", _) => Self::Help,
+            (cmd, []) => return Err(format!("unknown command `{cmd}`")),
+            (cmd, _) => return Err(format!("unexpected arguments for `{cmd}`")),
+        };
+        Ok(Some(command))
+    }
+
+    /// Execute the command against `workspace`, returning the process exit code.
+    pub fn execute(self, workspace: &Workspace) -> ExitCode {
+        match self {
+            Self::List => list(workspace),
+            Self::Run(id) => run(workspace, &id),
+            Self::Check => check(workspace),
+            Self::Test => test(workspace),
+            Self::Help => {
+                println!("{USAGE}");
+                ExitCode::SUCCESS
+            }
+        }
+    }
+}
+
+/// Print a usage error and return the matching exit code.
+pub fn usage_error(msg: &str) -> ExitCode {
+    eprintln!("error: {msg}\n\n{USAGE}");
+    ExitCode::from(2)
+}
+
+/// Load the example registry, printing manifest problems to stderr.
+fn load(workspace: &Workspace) -> Result<ExampleRegistry, ExitCode> {
+    let registry = ExampleRegistry::load(workspace).map_err(|err| {
+        eprintln!("error: {err}");
+        ExitCode::FAILURE
+    })?;
+    for problem in registry.problems() {
+        eprintln!("warning: {problem}");
+    }
+    Ok(registry)
+}
+
+fn list(workspace: &Workspace) -> ExitCode {
+    let registry = match load(workspace) {
+        Ok(registry) => registry,
+        Err(code) => return code,
+    };
+    for ex in registry.examples() {
+        println!("{:<16} {}", ex.id, ex.name);
+    }
+    ExitCode::SUCCESS
+}
+
+fn run(workspace: &Workspace, id: &str) -> ExitCode {
+    let registry = match load(workspace) {
+        Ok(registry) => registry,
+        Err(code) => return code,
+    };
+    let Some(ex) = registry.examples().iter().find(|e| e.id == id) else {
+        eprintln!("error: no example with id `{id}`");
+        return ExitCode::from(2);
+    };
+    let script = match std::fs::read_to_string(&ex.script_path) {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("error: cannot read {}: {e}", ex.script_path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let handle = ex.spawn_script(script);
+    for line in handle.output_lines() {
+        println!("{line}");
+    }
+    let result = handle.wait();
+    match result.error {
+        Some(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+        None => {
+            println!("=> {}", result.value);
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+fn check(workspace: &Workspace) -> ExitCode {
+    let registry = match load(workspace) {
+        Ok(registry) => registry,
+        Err(code) => return code,
+    };
+    let examples = registry.examples();
+    let mut failed = HashSet::new();
+    for ex in examples {
+        match ex.check() {
+            Ok(_) => println!("ok    {}", ex.id),
+            Err(err) => {
+                failed.insert(ex.id.as_str());
+                println!("FAIL  {}: {err}", ex.id);
+            }
+        }
+    }
+    let code = summary("compiled", examples.len(), failed.len());
+    // Problems such as missing files or duplicate ids fail the check as well.
+    match other_problem_entries(registry.problems(), &failed) {
+        0 => code,
+        n => {
+            println!("{n} more manifest entries have problems");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Number of manifest entries with problems, leaving out the examples in
+/// `failed`, whose scripts not compiling is reported as a problem as well.
+///
+/// Each example counts once however many problems it has.
+fn other_problem_entries(problems: &[ManifestProblem], failed: &HashSet<&str>) -> usize {
+    let mut examples = HashSet::new();
+    let mut unnamed = 0;
+    for problem in problems {
+        match problem.example_id.as_deref() {
+            Some(id) if failed.contains(id) => {}
+            Some(id) => {
+                examples.insert(id);
+            }
+            None => unnamed += 1,
+        }
+    }
+    examples.len() + unnamed
+}
+
+fn test(workspace: &Workspace) -> ExitCode {
+    let registry = match load(workspace) {
+        Ok(registry) => registry,
+        Err(code) => return code,
+    };
+    let examples = registry.examples();
+    let mut failed = 0;
+    for ex in examples {
+        match verify(ex) {
+            Ok(()) => println!("ok    {}", ex.id),
+            Err(msg) => {
+                failed += 1;
+                println!("FAIL  {}: {msg}", ex.id);
+            }
+        }
+    }
+    summary("passed", examples.len(), failed)
+}
+
+/// Check an example against its manifest expectations and run its `test_*`
+/// functions, describing the problems found.
+fn verify(ex: &Example) -> Result<(), String> {
+    ex.verify().map_err(|mismatches| mismatches.join("; "))?;
+    let script = std::fs::read_to_string(&ex.script_path)
+        .map_err(|e| format!("cannot read {}: {e}", ex.script_path.display()))?;
+    let report = ex.run_tests(&script);
+    if !report.is_success() {
+        return Err(format!(
+            "{} of {} test functions passed",
+            report.passed(),
+            report.cases.len()
+        ));
+    }
+    Ok(())
+}
+
+fn summary(verb: &str, total: usize, failed: usize) -> ExitCode {
+    println!("\n{} of {total} examples {verb}", total - failed);
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Command>, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        Command::parse(&args)
+    }
+
+    #[test]
+    fn parses_commands_and_rejects_bad_arguments() {
+        assert!(matches!(parse(&[]), Ok(None)));
+        assert!(matches!(parse(&["list"]), Ok(Some(Command::List))));
+        assert!(matches!(parse(&["run", "hello"]), Ok(Some(Command::Run(id))) if id == "hello"));
+        assert!(matches!(parse(&["check"]), Ok(Some(Command::Check))));
+        assert!(matches!(parse(&["test"]), Ok(Some(Command::Test))));
+        assert!(parse(&["run"]).is_err());
+        assert!(parse(&["list", "extra"]).is_err());
+        assert!(parse(&["bogus"]).is_err());
+    }
+
+    #[test]
+    fn takes_root_option_from_anywhere() {
+        let mut args = vec![
+            "--root".to_string(),
+            "/tmp/ws".to_string(),
+            "list".to_string(),
+        ];
+        assert_eq!(take_root(&mut args), Ok(Some(PathBuf::from("/tmp/ws"))));
+        assert_eq!(args, ["list"]);
+
+        let mut args = vec![
+            "run".to_string(),
+            "--root=ws".to_string(),
+            "hello".to_string(),
+        ];
+        assert_eq!(take_root(&mut args), Ok(Some(PathBuf::from("ws"))));
+        assert_eq!(args, ["run", "hello"]);
+
+        assert!(take_root(&mut vec!["--root".to_string()]).is_err());
+        assert_eq!(take_root(&mut vec!["list".to_string()]), Ok(None));
+    }
+
+    #[test]
+    fn failing_examples_count_once() {
+        let problem = |id: Option<&str>| ManifestProblem {
+            manifest: PathBuf::from("manifest.toml"),
+            example_id: id.map(str::to_string),
+            line: None,
+            message: String::new(),
+        };
+        let problems = [
+            problem(Some("broken")),
+            problem(Some("no-docs")),
+            problem(Some("no-docs")),
+            problem(None),
+        ];
+        let failed = HashSet::from(["broken"]);
+        assert_eq!(other_problem_entries(&problems, &failed), 2);
+    }
+}
@@ -0,0 +1,286 @@
+//! Step debugging of a script on a background thread, driven through
+//! Rhai's debugger interface.
+
+use super::{Example, RunHandle, RunResult, StackFrame, Variable, scope_variables};
+use crate::host::Limits;
+use rhai::debugger::{DebuggerCommand, DebuggerEvent};
+use rhai::{ASTNode, Stmt};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+use std::sync::{Arc, Mutex};
+
+/// A line to pause at, in the main script (`path` is `None`) or in a module file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Breakpoint {
+    /// Module file containing the line, `None` for the main script.
+    pub path: Option<PathBuf>,
+    /// Line to pause at, starting at 1.
+    pub line: usize,
+}
+
+/// How a paused script should go on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Pause at the next line, entering called functions.
+    StepInto,
+    /// Pause at the next line of the current function or its callers.
+    StepOver,
+    /// Pause once the current function has returned.
+    StepOut,
+}
+
+/// Where a script is paused.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pause {
+    /// Module the script is paused in, if not the main script.
+    pub source: Option<String>,
+    /// Line about to run, starting at 1.
+    pub line: usize,
+    /// Column of the statement or expression about to run, starting at 1.
+    pub column: usize,
+    /// Calls leading to the paused function, from the outermost to the innermost.
+    pub call_stack: Vec<StackFrame>,
+    /// Variables in scope of the paused function or script.
+    pub variables: Vec<Variable>,
+}
+
+/// Source, line and call depth of a step, to tell steps on different lines
+/// or in different calls apart.
+type StepLocation = (Option<String>, usize, usize);
+
+/// When to pause next.
+enum Mode {
+    Continue,
+    StepInto,
+    /// Pause at a call depth at most this one.
+    StepOver(usize),
+    /// Pause at a call depth below this one.
+    StepOut(usize),
+}
+
+/// Pausing state shared with the debugger callback.
+struct Stepper {
+    mode: Mode,
+    /// Location of the previous step and the column the visit to its line
+    /// started at.
+    previous: Option<(StepLocation, usize)>,
+}
+
+impl Stepper {
+    /// Whether to pause before the statement or expression at `here` and
+    /// `column`.
+    ///
+    /// Each visit to a line pauses at most once, at its first step. A visit
+    /// ends when a step is on another line, or when a statement starts at or
+    /// before the column the visit started at, as a loop body does on its
+    /// next iteration.
+    fn should_pause(
+        &mut self,
+        here: StepLocation,
+        column: usize,
+        is_stmt: bool,
+        breakpoint: bool,
+    ) -> bool {
+        let new_visit = match &self.previous {
+            Some((at, start)) if *at == here => is_stmt && column <= *start,
+            _ => true,
+        };
+        if !new_visit {
+            return false;
+        }
+        let depth = here.2;
+        self.previous = Some((here, column));
+        breakpoint
+            || match self.mode {
+                Mode::Continue => false,
+                Mode::StepInto => true,
+                Mode::StepOver(start) => depth <= start,
+                Mode::StepOut(start) => depth < start,
+            }
+    }
+
+    fn resume(&mut self, command: DebugCommand, depth: usize) {
+        self.mode = match command {
+            DebugCommand::Continue => Mode::Continue,
+            DebugCommand::StepInto => Mode::StepInto,
+            DebugCommand::StepOver => Mode::StepOver(depth),
+            DebugCommand::StepOut => Mode::StepOut(depth),
+        };
+    }
+}
+
+/// Handle to a script running under the debugger on a background thread.
+pub struct DebugSession {
+    run: RunHandle,
+    pause_rx: Receiver<Pause>,
+    command_tx: Sender<DebugCommand>,
+    breakpoints: Arc<Mutex<HashSet<Breakpoint>>>,
+    paused: Option<Pause>,
+}
+
+impl DebugSession {
+    /// The underlying run, for its output, result and elapsed time.
+    pub fn run(&self) -> &RunHandle {
+        &self.run
+    }
+
+    /// Where the script is paused, if it is.
+    ///
+    /// Picks up a new pause without blocking.
+    pub fn paused(&mut self) -> Option<&Pause> {
+        match self.pause_rx.try_recv() {
+            Ok(pause) => self.paused = Some(pause),
+            Err(TryRecvError::Disconnected) => self.paused = None,
+            Err(TryRecvError::Empty) => {}
+        }
+        self.paused.as_ref()
+    }
+
+    /// Block until the script pauses, returning `None` if it finishes first.
+    pub fn wait_for_pause(&mut self) -> Option<&Pause> {
+        self.paused = self.pause_rx.recv().ok();
+        self.paused.as_ref()
+    }
+
+    /// Resume a paused script. Does nothing while it is running.
+    pub fn resume(&mut self, command: DebugCommand) {
+        if self.paused.take().is_some() {
+            let _ = self.command_tx.send(command);
+        }
+    }
+
+    /// Replace the breakpoints, also while the script is running.
+    pub fn set_breakpoints(&self, breakpoints: impl IntoIterator<Item = Breakpoint>) {
+        if let Ok(mut set) = self.breakpoints.lock() {
+            *set = breakpoints.into_iter().collect();
+        }
+    }
+
+    /// Stop the script, also while it is paused.
+    pub fn stop(&mut self) {
+        self.run.cancel();
+        self.paused = None;
+        let _ = self.command_tx.send(DebugCommand::Continue);
+    }
+
+    /// Run to the end without pausing again and return the result.
+    pub fn wait(self) -> RunResult {
+        // Without anyone to report pauses to, the script no longer pauses.
+        drop(self.pause_rx);
+        drop(self.command_tx);
+        self.run.wait()
+    }
+}
+
+/// Start `script` under the debugger, pausing at the first step if
+/// `stop_on_entry` is set and otherwise at the first breakpoint reached.
+///
+/// The timeout of the example is not applied, since the time spent paused
+/// would count against it.
+pub(super) fn debug_script(
+    example: &Example,
+    script: String,
+    breakpoints: impl IntoIterator<Item = Breakpoint>,
+    stop_on_entry: bool,
+) -> DebugSession {
+    let (pause_tx, pause_rx) = channel();
+    let (command_tx, command_rx) = channel();
+    let command_rx = Mutex::new(command_rx);
+    let breakpoints: Arc<Mutex<HashSet<_>>> =
+        Arc::new(Mutex::new(breakpoints.into_iter().collect()));
+    let stepper = Mutex::new(Stepper {
+        mode: if stop_on_entry {
+            Mode::StepInto
+        } else {
+            Mode::Continue
+        },
+        previous: None,
+    });
+    let paths = Mutex::new(HashMap::new());
+    let resolver = example.clone();
+    let watched = breakpoints.clone();
+    let limits = Limits {
+        timeout_ms: None,
+        ..example.limits.clone()
+    };
+
+    let run = example.spawn_with(script, move |builder| {
+        builder
+            .limits(limits)
+            .debugger(move |context, event, node, source, pos| {
+                let (Some(line), Some(column)) = (pos.line(), pos.position()) else {
+                    return Ok(DebuggerCommand::StepInto);
+                };
+                // The first step of a script or module comes as its start.
+                if !matches!(event, DebuggerEvent::Start | DebuggerEvent::Step) {
+                    return Ok(DebuggerCommand::StepInto);
+                }
+                // Function bodies start with a no-op at their opening brace.
+                let is_stmt = match node {
+                    ASTNode::Stmt(Stmt::Noop(..)) => return Ok(DebuggerCommand::StepInto),
+                    ASTNode::Stmt(..) => true,
+                    _ => false,
+                };
+                let frames = context.global_runtime_state().debugger().call_stack();
+                let here = (source.map(str::to_string), line, frames.len());
+                let path = match source {
+                    Some(source) => paths.lock().ok().and_then(|mut cache| {
+                        cache
+                            .entry(source.to_string())
+                            .or_insert_with(|| resolver.source_path(source))
+                            .clone()
+                    }),
+                    None => None,
+                };
+                let breakpoint = Breakpoint { path, line };
+                let breakpoint = watched.lock().is_ok_and(|set| set.contains(&breakpoint));
+                let Ok(mut stepper) = stepper.lock() else {
+                    return Ok(DebuggerCommand::StepInto);
+                };
+                let depth = here.2;
+                if !stepper.should_pause(here.clone(), column, is_stmt, breakpoint) {
+                    return Ok(DebuggerCommand::StepInto);
+                }
+
+                let call_stack = frames
+                    .iter()
+                    .enumerate()
+                    .map(|(i, frame)| StackFrame {
+                        function: frame.fn_name.to_string(),
+                        // The call site is in the module of the calling function.
+                        source: i
+                            .checked_sub(1)
+                            .and_then(|caller| frames[caller].source.as_ref())
+                            .map(|s| s.to_string()),
+                        line: frame.pos.line(),
+                        column: frame.pos.position(),
+                    })
+                    .collect();
+                let pause = Pause {
+                    source: here.0.clone(),
+                    line,
+                    column,
+                    call_stack,
+                    variables: scope_variables(context.scope()),
+                };
+                let command = match pause_tx.send(pause) {
+                    Ok(()) => command_rx.lock().ok().and_then(|rx| rx.recv().ok()),
+                    Err(_) => None,
+                };
+                stepper.resume(command.unwrap_or(DebugCommand::Continue), depth);
+                // Always step so every statement and expression reaches this callback.
+                Ok(DebuggerCommand::StepInto)
+            })
+    });
+
+    DebugSession {
+        run,
+        pause_rx,
+        command_tx,
+        breakpoints,
+        paused: None,
+    }
+}
@@ -0,0 +1,161 @@
+//! Structured errors reported by example runs.
+
+use crate::host::{self, LimitKind};
+use rhai::{EvalAltResult, Position};
+use std::fmt;
+
+/// Broad category of a [`ScriptError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The script failed to compile.
+    Parse,
+    /// The script raised an error while running, including `throw`.
+    Runtime,
+    /// A host function refused the call, e.g. a missing capability.
+    Host,
+    /// A resource limit stopped the script.
+    Limit(LimitKind),
+    /// The run was cancelled by the user.
+    Cancelled,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse => f.write_str("Parse error"),
+            Self::Runtime => f.write_str("Runtime error"),
+            Self::Host => f.write_str("Host error"),
+            Self::Limit(limit) => write!(f, "Limit exceeded ({limit})"),
+            Self::Cancelled => f.write_str("Cancelled"),
+        }
+    }
+}
+
+/// One entry of the Rhai call stack at the point an error was raised.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackFrame {
+    /// Name of the called function, or the module path for a failed import.
+    pub function: String,
+    /// Module containing the call site, if not the main script.
+    pub source: Option<String>,
+    /// Line of the call site, starting at 1.
+    pub line: Option<usize>,
+    /// Column of the call site, starting at 1.
+    pub column: Option<usize>,
+}
+
+/// Error raised while compiling or running an example script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptError {
+    /// Category of the error.
+    pub kind: ErrorKind,
+    /// Human-readable message without position information.
+    pub message: String,
+    /// Line where the error was raised, starting at 1.
+    pub line: Option<usize>,
+    /// Column where the error was raised, starting at 1.
+    pub column: Option<usize>,
+    /// Module the error was raised in, if not the main script.
+    pub source: Option<String>,
+    /// Call stack from the outermost call to the innermost one.
+    pub call_stack: Vec<StackFrame>,
+    /// Id of the example that was running.
+    pub example_id: String,
+}
+
+impl ScriptError {
+    /// Convert a Rhai error into a structured error for `example_id`.
+    pub fn from_eval(example_id: &str, err: &EvalAltResult) -> Self {
+        let mut call_stack = Vec::new();
+        let mut source = None;
+        let mut current = err;
+        loop {
+            match current {
+                EvalAltResult::ErrorInFunctionCall(name, src, inner, pos) => {
+                    call_stack.push(frame(name, source.clone(), *pos));
+                    source = (!src.is_empty()).then(|| src.clone());
+                    current = inner;
+                }
+                EvalAltResult::ErrorInModule(path, inner, pos) => {
+                    call_stack.push(frame(path, source.clone(), *pos));
+                    source = (!path.is_empty()).then(|| path.clone());
+                    current = inner;
+                }
+                _ => break,
+            }
+        }
+
+        let kind = if host::is_cancelled(current) {
+            ErrorKind::Cancelled
+        } else if let Some(limit) = LimitKind::from_error(current) {
+            ErrorKind::Limit(limit)
+        } else {
+            match current {
+                EvalAltResult::ErrorParsing(..) => ErrorKind::Parse,
+                EvalAltResult::ErrorSystem(..) => ErrorKind::Host,
+                _ if host::not_granted_message(current).is_some() => ErrorKind::Host,
+                _ => ErrorKind::Runtime,
+            }
+        };
+
+        let pos = current.position();
+        let message = match current {
+            EvalAltResult::ErrorParsing(p, ..) => p.to_string(),
+            EvalAltResult::ErrorRuntime(value, ..) => {
+                host::not_granted_message(current).unwrap_or_else(|| value.to_string())
+            }
+            other => {
+                // Rhai appends the position to the message; it is reported separately.
+                let text = other.to_string();
+                let suffix = format!(" ({pos})");
+                match text.strip_suffix(&suffix) {
+                    Some(text) if !pos.is_none() => text.to_string(),
+                    _ => text,
+                }
+            }
+        };
+
+        Self {
+            kind,
+            message,
+            line: pos.line(),
+            column: pos.position(),
+            source,
+            call_stack,
+            example_id: example_id.to_string(),
+        }
+    }
+}
+
+fn frame(function: &str, source: Option<String>, pos: Position) -> StackFrame {
+    StackFrame {
+        function: function.to_string(),
+        source,
+        line: pos.line(),
+        column: pos.position(),
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)?;
+        if let Some(source) = &self.source {
+            write!(f, " in '{source}'")?;
+        }
+        if let Some(line) = self.line {
+            write!(f, " (line {line}, column {})", self.column.unwrap_or(0))?;
+        }
+        for frame in self.call_stack.iter().rev() {
+            write!(f, "\n  in call to '{}'", frame.function)?;
+            if let Some(source) = &frame.source {
+                write!(f, " in '{source}'")?;
+            }
+            if let Some(line) = frame.line {
+                write!(f, " (line {line}, column {})", frame.column.unwrap_or(0))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ScriptError {}
@@ -0,0 +1,110 @@
+//! Expected results declared for examples in `manifest.toml`.
+
+use super::RunResult;
+use serde::Deserialize;
+
+/// How the captured output of a run is checked.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputExpectation {
+    /// The output must equal this text.
+    Exact(String),
+    /// The output must contain this text.
+    Contains(String),
+    /// The output must match this regular expression.
+    Regex(String),
+}
+
+impl OutputExpectation {
+    /// Check `output`, describing the mismatch on failure.
+    fn check(&self, output: &str) -> Result<(), String> {
+        let ok = match self {
+            Self::Exact(text) => output == text,
+            Self::Contains(text) => output.contains(text.as_str()),
+            Self::Regex(pattern) => regex::Regex::new(pattern)
+                .map_err(|e| format!("invalid expected_stdout regex: {e}"))?
+                .is_match(output),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("stdout {output:?} does not satisfy {self:?}"))
+        }
+    }
+}
+
+/// Expected value, output and error of an example run.
+///
+/// When `error` is set the run must fail with a message containing it;
+/// otherwise the run must succeed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Expectations {
+    /// Value the script must return, the manifest's `expected_value`.
+    pub value: Option<toml::Value>,
+    /// Check applied to the captured `print`/`debug` output, the manifest's
+    /// `expected_stdout`.
+    pub stdout: Option<OutputExpectation>,
+    /// Text the error message must contain, the manifest's `expected_error`.
+    pub error: Option<String>,
+}
+
+impl Expectations {
+    /// Check a run against the expectations, returning every mismatch found.
+    pub fn check(&self, result: &RunResult) -> Result<(), Vec<String>> {
+        let mut mismatches = Vec::new();
+
+        match (&self.error, &result.error) {
+            (None, Some(err)) => mismatches.push(format!("unexpected error: {err}")),
+            (Some(expected), None) => {
+                mismatches.push(format!("expected an error containing {expected:?}"))
+            }
+            (Some(expected), Some(err)) if !err.message.contains(expected.as_str()) => mismatches
+                .push(format!(
+                    "error {:?} does not contain {expected:?}",
+                    err.message
+                )),
+            _ => {}
+        }
+
+        if let Some(expected) = &self.value {
+            let expected = serde_json::to_value(expected);
+            let actual = serde_json::to_value(&result.value);
+            match (expected, actual) {
+                (Ok(expected), Ok(actual)) if values_match(&expected, &actual) => {}
+                (Ok(expected), Ok(actual)) => {
+                    mismatches.push(format!("expected value {expected}, got {actual}"))
+                }
+                (Err(e), _) | (_, Err(e)) => mismatches.push(format!("cannot compare values: {e}")),
+            }
+        }
+
+        if let Some(stdout) = &self.stdout
+            && let Err(msg) = stdout.check(&result.stdout)
+        {
+            mismatches.push(msg);
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+}
+
+/// Compare JSON values, treating integers and floats with the same value as equal.
+fn values_match(expected: &serde_json::Value, actual: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    match (expected, actual) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_match(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, v)| b.get(k).is_some_and(|w| values_match(v, w)))
+        }
+        _ => expected == actual,
+    }
+}
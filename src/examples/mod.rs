@@ -1,62 +1,33 @@
 //! Utilities for loading, running, and documenting Rhai example scripts.
 
-use rand::Rng;
-use rhai::{AST, Dynamic, Engine, EvalAltResult, module_resolvers::FileModuleResolver};
-use serde::Deserialize;
+use crate::host::{Capability, FnInfo, FnOrigin, HostEngine, HostEngineBuilder, Limits};
+use rhai::{AST, Dynamic, EvalAltResult, Scope};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-
-#[derive(Clone)]
-struct Point {
-    x: i64,
-    y: i64,
-}
-
-impl Point {
-    fn new(x: i64, y: i64) -> Self {
-        Self { x, y }
-    }
-
-    fn length(&mut self) -> f64 {
-        ((self.x * self.x + self.y * self.y) as f64).sqrt()
-    }
-}
-
-fn http_get(url: &str) -> Dynamic {
-    match reqwest::blocking::get(url) {
-        Ok(resp) => match resp.json::<Dynamic>() {
-            Ok(json) => json,
-            Err(e) => format!("Error parsing JSON: {e}").into(),
-        },
-        Err(e) => format!("Request error: {e}").into(),
-    }
-}
-
-fn to_json(value: Dynamic) -> String {
-    serde_json::to_string(&value).unwrap_or_default()
-}
-
-fn from_json(s: &str) -> Dynamic {
-    serde_json::from_str::<Dynamic>(s).unwrap_or(Dynamic::UNIT)
-}
-
-fn assert_fn(cond: bool) {
-    if !cond {
-        panic!("assertion failed");
-    }
-}
-
-fn read_file(path: &str) -> String {
-    std::fs::read_to_string(path).unwrap_or_else(|e| format!("Error reading file: {e}"))
-}
-
-fn sleep_ms(ms: i64) {
-    std::thread::sleep(std::time::Duration::from_millis(ms as u64));
-}
-
-fn rand_int(min: i64, max: i64) -> i64 {
-    rand::thread_rng().gen_range(min..=max)
-}
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError, channel};
+use std::time::{Duration, Instant};
+
+mod debugger;
+mod error;
+mod expect;
+mod optimize;
+mod registry;
+mod syntax_tree;
+mod test_runner;
+mod variables;
+
+pub use debugger::{Breakpoint, DebugCommand, DebugSession, Pause};
+pub use error::{ErrorKind, ScriptError, StackFrame};
+pub use expect::{Expectations, OutputExpectation};
+pub use optimize::{LevelRun, OPTIMIZATION_LEVELS};
+pub use registry::{ExampleRegistry, ManifestError, ManifestProblem, Origin};
+pub use syntax_tree::{
+    AST_JSON_VERSION, AstExport, AstNode, FnDef, ImportDef, Literal, NodeKind, syntax_tree,
+    tree_outline,
+};
+pub use test_runner::{TEST_PREFIX, TestCase, TestOutcome, TestReport};
+pub use variables::{Variable, scope_variables};
 
 /// Metadata and execution support for a single Rhai example.
 #[derive(Clone, Debug)]
@@ -69,13 +40,27 @@ pub struct Example {
     pub description: String,
     /// Optional additional note.
     pub note: Option<String>,
+    /// Whether the example is bundled or belongs to the user workspace.
+    pub origin: Origin,
     /// Path to the Markdown documentation.
     #[allow(dead_code)]
     pub doc_path: PathBuf,
     /// Path to the rendered HTML documentation.
     pub doc_html_path: PathBuf,
-    /// Path to the Rhai script file.
+    /// Path to the Rhai script file, which is the user copy of a modified bundled example.
     pub script_path: PathBuf,
+    /// Shipped script of a bundled example, which is never written to.
+    pub bundled_script_path: Option<PathBuf>,
+    /// Where the script is saved, if there is a user workspace to save to.
+    pub user_script_path: Option<PathBuf>,
+    /// File the captured output of a run is written to.
+    pub log_path: PathBuf,
+    /// Resource limits applied when running the script.
+    pub limits: Limits,
+    /// Capabilities granted to the script.
+    pub capabilities: Vec<Capability>,
+    /// Expected value, output and error of a run.
+    pub expectations: Expectations,
 }
 
 /// Result of running a Rhai example.
@@ -86,214 +71,454 @@ pub struct RunResult {
     pub value: Dynamic,
     /// Compiled AST of the script.
     pub ast: AST,
-    /// Error string when evaluation fails.
-    pub error: Option<String>,
+    /// Variables left in scope when the script finished or failed.
+    pub scope: Scope<'static>,
+    /// Error raised when compilation or evaluation fails.
+    pub error: Option<ScriptError>,
 }
 
-impl Example {
-    /// Run this example's script, capturing any printed output and returning the result.
-    pub fn run(&self) -> Result<Dynamic, Box<EvalAltResult>> {
-        let stdout = Arc::new(Mutex::new(String::new()));
-        let mut engine = Engine::new();
-        let mut resolver = FileModuleResolver::new();
-        if let Some(parent) = self.script_path.parent() {
-            resolver.set_base_path(parent);
+/// Handle to an example script running on a background thread.
+pub struct RunHandle {
+    example_id: String,
+    result_rx: Receiver<RunResult>,
+    output_rx: Receiver<String>,
+    cancel: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl RunHandle {
+    /// Ask the running script to stop at its next operation.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Time since the run was started.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Output lines produced since the last call, without blocking.
+    pub fn new_output(&self) -> impl Iterator<Item = String> + '_ {
+        self.output_rx.try_iter()
+    }
+
+    /// Output lines as they are produced, blocking until the script finishes.
+    pub fn output_lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.output_rx.iter()
+    }
+
+    /// Block until the run has finished and return its result.
+    pub fn wait(self) -> RunResult {
+        self.result_rx
+            .recv()
+            .unwrap_or_else(|_| RunResult::worker_stopped(&self.example_id))
+    }
+
+    /// Return the result if the run has finished, without blocking.
+    pub fn try_result(&self) -> Option<RunResult> {
+        match self.result_rx.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(RunResult::worker_stopped(&self.example_id)),
         }
-        engine.set_module_resolver(resolver);
-        // Capture calls to `print` into our stdout buffer.
-        let out = stdout.clone();
-        engine.on_print(move |s| {
-            if let Ok(mut buf) = out.lock() {
-                buf.push_str(s);
-                buf.push('\n');
-            }
-        });
+    }
+}
 
-        // Capture debug output as well.
-        let out_dbg = stdout.clone();
-        engine.on_debug(move |s, _, _| {
-            if let Ok(mut buf) = out_dbg.lock() {
-                buf.push_str("DEBUG: ");
-                buf.push_str(s);
-                buf.push('\n');
-            }
-        });
+impl RunResult {
+    /// The compiled AST as stable JSON, see [`AstExport`].
+    pub fn ast_json(&self) -> String {
+        AstExport::new(&self.ast).to_json()
+    }
 
-        // Register custom Rust types and helper functions.
-        engine.register_type::<Point>();
-        engine.register_fn("Point", Point::new);
-        engine.register_fn("length", Point::length);
-        engine.register_fn("http_get", http_get);
-        engine.register_fn("to_json", to_json);
-        engine.register_fn("from_json", from_json);
-        engine.register_fn("assert", assert_fn);
-        engine.register_fn("read_file", read_file);
-        engine.register_fn("sleep_ms", sleep_ms);
-        engine.register_fn("rand_int", rand_int);
+    /// The variables of the final [`scope`](Self::scope).
+    pub fn variables(&self) -> Vec<Variable> {
+        scope_variables(&self.scope)
+    }
 
-        // Evaluate the script file so relative imports work.
-        let result = engine.eval_file::<Dynamic>(self.script_path.clone());
+    /// Result reported when the worker thread exits without sending one.
+    fn worker_stopped(example_id: &str) -> Self {
+        Self {
+            stdout: String::new(),
+            value: Dynamic::UNIT,
+            ast: AST::empty(),
+            scope: Scope::new(),
+            error: Some(ScriptError {
+                kind: ErrorKind::Host,
+                message: "script worker stopped unexpectedly".to_string(),
+                line: None,
+                column: None,
+                source: None,
+                call_stack: Vec::new(),
+                example_id: example_id.to_string(),
+            }),
+        }
+    }
+}
 
-        let stdout = stdout.lock().map(|s| s.clone()).unwrap_or_default();
+impl Example {
+    /// Create a host engine that resolves imports next to this example's script,
+    /// enforces its resource limits and grants its capabilities.
+    pub fn host_engine(&self) -> HostEngine {
+        self.host_builder().build()
+    }
 
-        if !stdout.is_empty() {
-            let log_dir = std::path::Path::new("logs");
-            let _ = std::fs::create_dir_all(log_dir);
-            let log_path = log_dir.join(format!("{}.log", self.id));
-            let _ = std::fs::write(log_path, &stdout);
+    fn host_builder(&self) -> HostEngineBuilder {
+        let mut builder = HostEngine::builder()
+            .limits(self.limits.clone())
+            .capabilities(self.capabilities.iter().copied());
+        for dir in self.module_dirs() {
+            builder = builder.module_base_path(dir);
         }
+        builder
+    }
 
-        result
+    /// Directory that `import` and `read_file` paths of the script resolve against.
+    ///
+    /// For a modified bundled example this stays next to the original, so its
+    /// modules and data files are still found.
+    pub fn module_dir(&self) -> Option<&Path> {
+        self.bundled_script_path
+            .as_deref()
+            .unwrap_or(&self.script_path)
+            .parent()
     }
 
-    /// Run a provided script text for this example instead of reading from file.
+    /// Directories searched for modules and data files, in order.
     ///
-    /// The script is executed with the same engine configuration as [`run`].
-    pub fn run_script(&self, script: &str) -> RunResult {
-        let stdout = Arc::new(Mutex::new(String::new()));
-        let mut engine = Engine::new();
-        let mut resolver = FileModuleResolver::new();
-        if let Some(parent) = self.script_path.parent() {
-            resolver.set_base_path(parent);
+    /// For a bundled example, edited copies in the user workspace come before
+    /// the bundled originals.
+    pub fn module_dirs(&self) -> Vec<&Path> {
+        let user_dir = self
+            .bundled_script_path
+            .as_ref()
+            .and(self.user_script_path.as_deref())
+            .and_then(Path::parent);
+        user_dir.into_iter().chain(self.module_dir()).collect()
+    }
+
+    /// Find `relative` in the [`module_dirs`](Self::module_dirs), falling back
+    /// to the bundled location if no directory has it.
+    pub fn resolve_file(&self, relative: &str) -> Option<PathBuf> {
+        let dirs = self.module_dirs();
+        dirs.iter()
+            .map(|dir| dir.join(relative))
+            .find(|p| p.is_file())
+            .or_else(|| self.module_dir().map(|dir| dir.join(relative)))
+    }
+
+    /// File of the module `source` reported by Rhai, resolved against the
+    /// [`module_dirs`](Self::module_dirs) and with the `.rhai` extension
+    /// added if it has none.
+    pub fn source_path(&self, source: &str) -> Option<PathBuf> {
+        let path = Path::new(source);
+        if path.is_absolute() {
+            return Some(path.to_path_buf());
         }
-        engine.set_module_resolver(resolver);
-        // Capture calls to `print` into our stdout buffer.
-        let out = stdout.clone();
-        engine.on_print(move |s| {
-            if let Ok(mut buf) = out.lock() {
-                buf.push_str(s);
-                buf.push('\n');
-            }
-        });
+        let mut file = source.to_string();
+        if path.extension().is_none() {
+            file.push_str(".rhai");
+        }
+        self.resolve_file(&file)
+    }
 
-        // Capture debug output as well.
-        let out_dbg = stdout.clone();
-        engine.on_debug(move |s, _, _| {
-            if let Ok(mut buf) = out_dbg.lock() {
-                buf.push_str("DEBUG: ");
-                buf.push_str(s);
-                buf.push('\n');
-            }
-        });
+    /// Whether a user copy shadows the script of a bundled example.
+    pub fn is_modified(&self) -> bool {
+        self.bundled_script_path.is_some()
+            && self.user_script_path.as_ref() == Some(&self.script_path)
+    }
+
+    /// Save `text` as the example's script.
+    ///
+    /// A bundled example is saved to a copy in the user workspace that
+    /// shadows the original from then on.
+    pub fn save_script(&mut self, text: &str) -> std::io::Result<()> {
+        let path = self.user_script_path.clone().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "bundled examples are read-only without a user workspace",
+            )
+        })?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, text)?;
+        self.script_path = path;
+        Ok(())
+    }
 
-        // Register custom Rust types and helper functions.
-        engine.register_type::<Point>();
-        engine.register_fn("Point", Point::new);
-        engine.register_fn("length", Point::length);
-        engine.register_fn("http_get", http_get);
-        engine.register_fn("to_json", to_json);
-        engine.register_fn("from_json", from_json);
-        engine.register_fn("assert", assert_fn);
-        engine.register_fn("read_file", read_file);
-        engine.register_fn("sleep_ms", sleep_ms);
-        engine.register_fn("rand_int", rand_int);
-
-        // Compile the provided script text and keep the AST.
-        let (ast, value, error) = match engine.compile(script) {
-            Ok(ast) => match engine.eval_ast::<Dynamic>(&ast) {
-                Ok(v) => (ast, v, None),
-                Err(e) => (ast, Dynamic::UNIT, Some(format!("{:?}", e))),
-            },
-            Err(e) => (AST::empty(), Dynamic::UNIT, Some(format!("{:?}", e))),
+    /// Delete the user copy of a modified bundled example, going back to the original.
+    pub fn reset_to_original(&mut self) -> std::io::Result<()> {
+        let Some(original) = self.bundled_script_path.clone() else {
+            return Ok(());
         };
+        if self.is_modified() {
+            std::fs::remove_file(&self.script_path)?;
+        }
+        self.script_path = original;
+        Ok(())
+    }
 
-        let stdout = stdout.lock().map(|s| s.clone()).unwrap_or_default();
+    /// Run this example's script, capturing any printed output and returning the result.
+    pub fn run(&self) -> Result<Dynamic, Box<ScriptError>> {
+        let host = self.host_engine();
+        let compiled = host.engine().compile_file(self.script_path.clone());
+        let (_, _, result) = self.execute(&host, compiled);
+        result.map_err(|e| ScriptError::from_eval(&self.id, &e).into())
+    }
 
-        if !stdout.is_empty() {
-            let log_dir = std::path::Path::new("logs");
-            let _ = std::fs::create_dir_all(log_dir);
-            let log_path = log_dir.join(format!("{}.log", self.id));
-            let _ = std::fs::write(log_path, &stdout);
+    /// Run a provided script text for this example instead of reading from file.
+    ///
+    /// The script is executed with the same engine configuration as [`run`](Self::run).
+    pub fn run_script(&self, script: &str) -> RunResult {
+        self.run_script_on(&self.host_engine(), script)
+    }
+
+    /// Run a provided script text on a background thread.
+    ///
+    /// The returned [`RunHandle`] streams output lines as they are printed,
+    /// can cancel the run and delivers its [`RunResult`].
+    pub fn spawn_script(&self, script: String) -> RunHandle {
+        self.spawn_with(script, |builder| builder)
+    }
+
+    /// Run `script` under the debugger on a background thread.
+    ///
+    /// The returned [`DebugSession`] pauses at the given breakpoints, or at
+    /// the first statement with `stop_on_entry`, and is stepped from there.
+    /// Breakpoints in modules the script imports are matched by their
+    /// [`source_path`](Self::source_path).
+    pub fn debug_script(
+        &self,
+        script: String,
+        breakpoints: impl IntoIterator<Item = Breakpoint>,
+        stop_on_entry: bool,
+    ) -> DebugSession {
+        debugger::debug_script(self, script, breakpoints, stop_on_entry)
+    }
+
+    /// Like [`spawn_script`](Self::spawn_script), with `configure` applied
+    /// to the engine builder on the worker thread.
+    fn spawn_with(
+        &self,
+        script: String,
+        configure: impl FnOnce(HostEngineBuilder) -> HostEngineBuilder + Send + 'static,
+    ) -> RunHandle {
+        let (tx, rx) = channel();
+        let (line_tx, line_rx) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let example = self.clone();
+        let flag = cancel.clone();
+        std::thread::spawn(move || {
+            let builder = example
+                .host_builder()
+                .cancel_flag(flag)
+                .on_output(move |line| {
+                    let _ = line_tx.send(line.to_string());
+                });
+            let host = configure(builder).build();
+            let result = example.run_script_on(&host, &script);
+            // Drop the engine first so the output stream ends before the result arrives.
+            drop(host);
+            let _ = tx.send(result);
+        });
+        RunHandle {
+            example_id: self.id.clone(),
+            result_rx: rx,
+            output_rx: line_rx,
+            cancel,
+            started: Instant::now(),
         }
+    }
+
+    fn run_script_on(&self, host: &HostEngine, script: &str) -> RunResult {
+        let compiled = host.engine().compile(script).map_err(Into::into);
+        let (ast, scope, result) = self.execute(host, compiled);
+        let stdout = host.output();
+        let (value, error) = match result {
+            Ok(v) => (v, None),
+            Err(e) => (Dynamic::UNIT, Some(ScriptError::from_eval(&self.id, &e))),
+        };
 
         RunResult {
             stdout,
             value,
             ast,
+            scope,
             error,
         }
     }
-}
-
-#[derive(Deserialize)]
-struct Manifest {
-    examples: Vec<ManifestEntry>,
-}
 
-#[derive(Deserialize)]
-struct ManifestEntry {
-    id: String,
-    name: String,
-    script: String,
-    doc: String,
-}
+    /// Run this example's script file and check it against its expectations.
+    ///
+    /// Returns every mismatch found, or the error that prevented the run.
+    pub fn verify(&self) -> Result<(), Vec<String>> {
+        let script = std::fs::read_to_string(&self.script_path)
+            .map_err(|e| vec![format!("cannot read {}: {e}", self.script_path.display())])?;
+        self.expectations.check(&self.run_script(&script))
+    }
 
-/// Registry of examples loaded from the manifest file.
-pub struct ExampleRegistry {
-    examples: Vec<Example>,
-}
+    /// Compile this example's script file without running it.
+    pub fn check(&self) -> Result<AST, Box<ScriptError>> {
+        let host = self.host_engine();
+        host.engine()
+            .compile_file(self.script_path.clone())
+            .map_err(|e| ScriptError::from_eval(&self.id, &e).into())
+    }
 
-impl ExampleRegistry {
-    /// Load the example registry from `examples/manifest.toml`.
-    pub fn load() -> Self {
-        let manifest_path = Path::new("examples/manifest.toml");
-        let data =
-            std::fs::read_to_string(manifest_path).expect("failed to read examples manifest");
-        let manifest: Manifest = toml::from_str(&data).expect("failed to parse examples manifest");
+    /// Compile a provided script text without running it.
+    ///
+    /// The script is compiled with the same engine configuration as
+    /// [`run_script`](Self::run_script).
+    pub fn check_script(&self, script: &str) -> Result<AST, Box<ScriptError>> {
+        let host = self.host_engine();
+        host.engine()
+            .compile(script)
+            .map_err(|e| ScriptError::from_eval(&self.id, &e.into()).into())
+    }
 
-        let examples = manifest
-            .examples
+    /// Describe the public functions of each module that `script` imports.
+    ///
+    /// Modules that cannot be read or do not compile are left out.
+    pub fn module_functions(&self, script: &str) -> Vec<(Import, Vec<FnInfo>)> {
+        let host = self.host_engine();
+        script_imports(script)
             .into_iter()
-            .map(|m| {
-                let doc_html_path = PathBuf::from(&m.doc);
-                let doc_path = doc_html_path.with_extension("md");
-                let script_path = PathBuf::from(&m.script);
-                let (description, note) = parse_doc(&doc_path);
-                Example {
-                    id: m.id,
-                    name: m.name,
-                    description,
-                    note,
-                    doc_path,
-                    doc_html_path,
-                    script_path,
+            .filter_map(|import| {
+                let mut file = import.path.clone();
+                if Path::new(&file).extension().is_none() {
+                    file.push_str(".rhai");
                 }
+                let path = self.resolve_file(&file).unwrap_or_else(|| file.into());
+                let source = std::fs::read_to_string(path).ok()?;
+                let ast = host.engine().compile(source).ok()?;
+                let functions = host
+                    .functions(Some(&ast))
+                    .into_iter()
+                    .filter(|f| f.origin == FnOrigin::Script && !f.is_private())
+                    .collect();
+                Some((import, functions))
             })
-            .collect();
+            .collect()
+    }
 
-        Self { examples }
+    /// Compile and run `script` under each of the [`OPTIMIZATION_LEVELS`], with
+    /// the same engine configuration as [`run_script`](Self::run_script)
+    /// otherwise.
+    ///
+    /// The script runs once per level, so its side effects happen each time.
+    pub fn compare_optimizations(&self, script: &str) -> Vec<LevelRun> {
+        optimize::compare_levels(self, script)
     }
-}
 
-/// Parse a markdown document for description and optional note.
-fn parse_doc(path: &Path) -> (String, Option<String>) {
-    let text = std::fs::read_to_string(path).unwrap_or_default();
-    let mut description = String::new();
-    let mut note = None;
+    /// Discover and run the `test_*` functions in `script` and in the modules it imports.
+    ///
+    /// Each test function is called in isolation with the same engine
+    /// configuration as [`run_script`](Self::run_script).
+    pub fn run_tests(&self, script: &str) -> TestReport {
+        test_runner::run_tests(self, script)
+    }
 
-    for line in text.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') {
-            continue;
+    /// Evaluate a compiled script and write any captured output to [`log_path`](Self::log_path).
+    ///
+    /// Returns the AST and the scope the script left behind with its result.
+    fn execute(
+        &self,
+        host: &HostEngine,
+        compiled: Result<AST, Box<EvalAltResult>>,
+    ) -> (AST, Scope<'static>, Result<Dynamic, Box<EvalAltResult>>) {
+        let mut scope = Scope::new();
+        let (ast, result) = match compiled {
+            Ok(ast) => {
+                let result = host
+                    .engine()
+                    .eval_ast_with_scope::<Dynamic>(&mut scope, &ast);
+                (ast, result)
+            }
+            Err(e) => (AST::empty(), Err(e)),
+        };
+
+        let stdout = host.output();
+        if !stdout.is_empty() {
+            if let Some(log_dir) = self.log_path.parent() {
+                let _ = std::fs::create_dir_all(log_dir);
+            }
+            let _ = std::fs::write(&self.log_path, &stdout);
         }
-        if description.is_empty() {
-            description = line.to_string();
-        } else if line.to_lowercase().starts_with("note:") {
-            note = Some(line[5..].trim().to_string());
-            break;
+
+        (ast, scope, result)
+    }
+}
+
+/// A static `import "path" as alias;` statement found in a script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+    /// Module path as written in the script.
+    pub path: String,
+    /// Name the module is bound to, if any.
+    pub alias: Option<String>,
+    /// Line of the statement, starting at 1.
+    pub line: usize,
+}
+
+impl Import {
+    /// Render the import back into a Rhai statement.
+    pub fn to_statement(&self) -> String {
+        match &self.alias {
+            Some(alias) => format!("import {:?} as {alias};\n", self.path),
+            None => format!("import {:?};\n", self.path),
         }
     }
+}
 
-    (description, note)
+/// Find the string literal paths passed to `read_file` in a script.
+pub fn script_read_files(script: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    for part in script.split("read_file").skip(1) {
+        let Some(rest) = part.trim_start().strip_prefix('(') else {
+            continue;
+        };
+        let Some(rest) = rest.trim_start().strip_prefix('"') else {
+            continue;
+        };
+        if let Some(end) = rest.find('"') {
+            paths.push(rest[..end].to_string());
+        }
+    }
+    paths
 }
 
-impl ExampleRegistry {
-    /// Return all examples sorted by id.
-    pub fn all() -> Vec<Example> {
-        let mut registry = Self::load();
-        registry.examples.sort_by(|a, b| a.id.cmp(&b.id));
-        registry.examples
+/// Find the `import` statements with a string literal path in a script.
+pub fn script_imports(script: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    for (i, line) in script.lines().enumerate() {
+        let Some(rest) = line.trim_start().strip_prefix("import") else {
+            continue;
+        };
+        let Some(rest) = rest.trim_start().strip_prefix('"') else {
+            continue;
+        };
+        let Some(end) = rest.find('"') else {
+            continue;
+        };
+        let path = rest[..end].to_string();
+        let alias = rest[end + 1..]
+            .trim_start()
+            .strip_prefix("as")
+            .filter(|r| r.starts_with(char::is_whitespace))
+            .map(|r| {
+                r.trim_start()
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect::<String>()
+            })
+            .filter(|a| !a.is_empty());
+        imports.push(Import {
+            path,
+            alias,
+            line: i + 1,
+        });
     }
+    imports
 }
 
 #[cfg(test)]
@@ -314,7 +539,7 @@ mod tests {
             "async-sim",
         ];
 
-        let registry = ExampleRegistry::all();
+        let registry = ExampleRegistry::all().expect("bundled manifest loads");
         for id in ids {
             let ex = registry
                 .iter()
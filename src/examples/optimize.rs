@@ -0,0 +1,73 @@
+//! Compilation and evaluation of a script under each optimization level.
+
+use super::{AstNode, Example, RunResult, ScriptError, syntax_tree, tree_outline};
+use rhai::{AST, Dynamic, OptimizationLevel, Scope};
+use std::time::{Duration, Instant};
+
+/// Optimization levels compared by [`Example::compare_optimizations`], from
+/// none to full.
+pub const OPTIMIZATION_LEVELS: [OptimizationLevel; 3] = [
+    OptimizationLevel::None,
+    OptimizationLevel::Simple,
+    OptimizationLevel::Full,
+];
+
+/// Outcome of compiling and running a script under one optimization level.
+pub struct LevelRun {
+    /// Level the script was compiled with.
+    pub level: OptimizationLevel,
+    /// Syntax tree of the optimized AST.
+    pub tree: Vec<AstNode>,
+    /// [`tree_outline`] of `tree`, for comparison between levels.
+    pub outline: String,
+    /// Output, value, AST and error of the run.
+    pub result: RunResult,
+    /// Time spent compiling and optimizing.
+    pub compile_time: Duration,
+    /// Time spent evaluating the compiled AST.
+    pub run_time: Duration,
+}
+
+/// Compile and run `script` once per level of [`OPTIMIZATION_LEVELS`].
+pub(super) fn compare_levels(example: &Example, script: &str) -> Vec<LevelRun> {
+    OPTIMIZATION_LEVELS
+        .iter()
+        .map(|&level| {
+            let host = example.host_builder().optimization_level(level).build();
+            let started = Instant::now();
+            let compiled = host.engine().compile(script);
+            let compile_time = started.elapsed();
+            let started = Instant::now();
+            let mut scope = Scope::new();
+            let (ast, result) = match compiled {
+                Ok(ast) => {
+                    let result = host
+                        .engine()
+                        .eval_ast_with_scope::<Dynamic>(&mut scope, &ast);
+                    (ast, result)
+                }
+                Err(e) => (AST::empty(), Err(e.into())),
+            };
+            let run_time = started.elapsed();
+            let (value, error) = match result {
+                Ok(v) => (v, None),
+                Err(e) => (Dynamic::UNIT, Some(ScriptError::from_eval(&example.id, &e))),
+            };
+            let tree = syntax_tree(&ast);
+            LevelRun {
+                level,
+                outline: tree_outline(&tree),
+                tree,
+                result: RunResult {
+                    stdout: host.output(),
+                    value,
+                    ast,
+                    scope,
+                    error,
+                },
+                compile_time,
+                run_time,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,314 @@
+//! Loading and validation of the example manifest.
+
+use super::{Example, Expectations, OutputExpectation};
+use crate::host::{Capability, Limits};
+use crate::workspace::Workspace;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+struct Manifest {
+    examples: Vec<toml::Spanned<toml::Value>>,
+}
+
+/// One `[[examples]]` entry. Unknown keys are rejected, so a misspelt
+/// expectation is reported rather than never checked.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestEntry {
+    id: String,
+    name: String,
+    script: String,
+    doc: String,
+    #[serde(default)]
+    limits: Limits,
+    #[serde(default)]
+    capabilities: Vec<Capability>,
+    expected_value: Option<toml::Value>,
+    expected_stdout: Option<OutputExpectation>,
+    expected_error: Option<String>,
+}
+
+/// The manifest could not be read or is not valid TOML, so no examples were loaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestError {
+    /// Path of the manifest file.
+    pub path: PathBuf,
+    /// Line of the error, starting at 1.
+    pub line: Option<usize>,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+/// A problem with one manifest entry found while loading the registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestProblem {
+    /// Path of the manifest containing the entry.
+    pub manifest: PathBuf,
+    /// Id of the affected example, if the entry has one.
+    pub example_id: Option<String>,
+    /// Line of the entry in the manifest, starting at 1.
+    pub line: Option<usize>,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for ManifestProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.manifest.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        f.write_str(": ")?;
+        if let Some(id) = &self.example_id {
+            write!(f, "{id}: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Where the manifest entry of an example comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Origin {
+    /// Shipped with the application; its files are never written to.
+    Bundled,
+    /// Listed in the manifest of the user workspace.
+    User,
+}
+
+/// Registry of examples merged from the bundled and user manifests.
+pub struct ExampleRegistry {
+    examples: Vec<Example>,
+    problems: Vec<ManifestProblem>,
+}
+
+impl ExampleRegistry {
+    /// Load and validate the examples of `workspace`.
+    ///
+    /// The bundled manifest must be readable. Examples from the optional
+    /// manifest in the user workspace are added to them, replacing bundled
+    /// examples with the same id, and edited copies of bundled scripts in the
+    /// user workspace shadow the originals.
+    ///
+    /// Script and documentation paths are relative to their manifest.
+    /// Entries that cannot be used — invalid fields, duplicate ids or a
+    /// missing script — are skipped. Missing documentation and scripts that
+    /// do not compile are kept so they can be fixed in the editor. Every
+    /// problem found is listed in [`problems`](Self::problems).
+    pub fn load(workspace: &Workspace) -> Result<Self, ManifestError> {
+        let mut registry = Self {
+            examples: Vec::new(),
+            problems: Vec::new(),
+        };
+        let path = workspace.manifest_path();
+        let (data, manifest) = read_manifest(&path)?;
+        registry.add_manifest(workspace, Origin::Bundled, &path, &data, manifest);
+
+        if let Some(path) = workspace.user_manifest_path()
+            && path.is_file()
+        {
+            match read_manifest(&path) {
+                Ok((data, manifest)) => {
+                    registry.add_manifest(workspace, Origin::User, &path, &data, manifest)
+                }
+                Err(err) => registry.problems.push(ManifestProblem {
+                    manifest: err.path,
+                    example_id: None,
+                    line: err.line,
+                    message: err.message,
+                }),
+            }
+        }
+
+        registry.examples.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(registry)
+    }
+
+    /// Validate the entries of one manifest and add the usable ones.
+    fn add_manifest(
+        &mut self,
+        workspace: &Workspace,
+        origin: Origin,
+        path: &Path,
+        data: &str,
+        manifest: Manifest,
+    ) {
+        let base = path.parent().unwrap_or(Path::new("."));
+        let mut ids = HashSet::new();
+        for entry in manifest.examples {
+            let line = Some(line_of(data, entry.span().start));
+            let entry = match ManifestEntry::deserialize(entry.into_inner()) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.problems.push(ManifestProblem {
+                        manifest: path.to_path_buf(),
+                        example_id: None,
+                        line,
+                        message: e.message().trim().to_string(),
+                    });
+                    continue;
+                }
+            };
+            let id = entry.id.clone();
+            let problem = |message: String| ManifestProblem {
+                manifest: path.to_path_buf(),
+                example_id: Some(id.clone()),
+                line,
+                message,
+            };
+
+            if !ids.insert(id.clone()) {
+                let message = "duplicate example id; entry ignored".to_string();
+                self.problems.push(problem(message));
+                continue;
+            }
+            let example = Example::from_entry(entry, origin, base, workspace);
+            if !example.script_path.is_file() {
+                let message = format!("script {} not found", example.script_path.display());
+                self.problems.push(problem(message));
+                continue;
+            }
+            for doc in [&example.doc_path, &example.doc_html_path] {
+                if !doc.is_file() {
+                    let message = format!("documentation {} not found", doc.display());
+                    self.problems.push(problem(message));
+                }
+            }
+            if let Err(err) = example.check() {
+                let message = format!("script does not compile: {err}");
+                self.problems.push(problem(message));
+            }
+            self.examples.retain(|e| e.id != example.id);
+            self.examples.push(example);
+        }
+    }
+
+    /// Valid examples sorted by id.
+    pub fn examples(&self) -> &[Example] {
+        &self.examples
+    }
+
+    /// Problems found in the manifest entries.
+    pub fn problems(&self) -> &[ManifestProblem] {
+        &self.problems
+    }
+
+    /// Consume the registry, returning its examples sorted by id.
+    pub fn into_examples(self) -> Vec<Example> {
+        self.examples
+    }
+
+    /// Return all valid bundled examples of this source tree sorted by id.
+    ///
+    /// Only the crate's own `examples/` directory is read: the user's
+    /// configuration, environment and data directory are ignored, so no user
+    /// copy shadows a bundled example and logs go to the crate's `logs/`.
+    ///
+    /// Problems with single entries are ignored; use [`load`](Self::load)
+    /// to report them.
+    pub fn all() -> Result<Vec<Example>, ManifestError> {
+        Self::load(&Workspace::new(env!("CARGO_MANIFEST_DIR"))).map(Self::into_examples)
+    }
+}
+
+/// Read and parse the manifest at `path`, returning its text as well.
+fn read_manifest(path: &Path) -> Result<(String, Manifest), ManifestError> {
+    let error = |line, message| ManifestError {
+        path: path.to_path_buf(),
+        line,
+        message,
+    };
+    let data = std::fs::read_to_string(path).map_err(|e| error(None, e.to_string()))?;
+    let manifest = toml::from_str(&data).map_err(|e| {
+        let line = e.span().map(|span| line_of(&data, span.start));
+        error(line, e.message().to_string())
+    })?;
+    Ok((data, manifest))
+}
+
+impl Example {
+    fn from_entry(m: ManifestEntry, origin: Origin, base: &Path, workspace: &Workspace) -> Self {
+        let doc_html_path = base.join(&m.doc);
+        let doc_path = doc_html_path.with_extension("md");
+        let log_path = workspace.logs_dir().join(format!("{}.log", m.id));
+        let (description, note) = parse_doc(&doc_path);
+
+        let (script_path, bundled_script_path, user_script_path) = match origin {
+            Origin::Bundled => {
+                let original = base.join(&m.script);
+                let copy = workspace.user_examples_dir().map(|d| d.join(&m.script));
+                let current = copy
+                    .clone()
+                    .filter(|c| c.is_file())
+                    .unwrap_or(original.clone());
+                (current, Some(original), copy)
+            }
+            Origin::User => {
+                let path = base.join(&m.script);
+                (path.clone(), None, Some(path))
+            }
+        };
+
+        Example {
+            id: m.id,
+            name: m.name,
+            description,
+            note,
+            origin,
+            doc_path,
+            doc_html_path,
+            script_path,
+            bundled_script_path,
+            user_script_path,
+            log_path,
+            limits: m.limits,
+            capabilities: m.capabilities,
+            expectations: Expectations {
+                value: m.expected_value,
+                stdout: m.expected_stdout,
+                error: m.expected_error,
+            },
+        }
+    }
+}
+
+/// Line number, starting at 1, of the byte offset `pos` in `text`.
+fn line_of(text: &str, pos: usize) -> usize {
+    text[..pos.min(text.len())].matches('\n').count() + 1
+}
+
+/// Parse a markdown document for description and optional note.
+fn parse_doc(path: &Path) -> (String, Option<String>) {
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    let mut description = String::new();
+    let mut note = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if description.is_empty() {
+            description = line.to_string();
+        } else if line.to_lowercase().starts_with("note:") {
+            note = Some(line[5..].trim().to_string());
+            break;
+        }
+    }
+
+    (description, note)
+}
@@ -0,0 +1,564 @@
+//! A simplified tree of the statements and expressions of a compiled script,
+//! and its JSON export.
+
+use rhai::{
+    AST, ASTFlags, ASTNode, Array, Dynamic, Expr, FnAccess, Map, Position, ScriptFuncDef, Stmt,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Version of the JSON written by [`AstExport::to_json`], increased whenever a
+/// field or node kind is renamed or removed, or its meaning changes.
+pub const AST_JSON_VERSION: u32 = 1;
+
+/// Kind of an [`AstNode`], written to JSON as its name, e.g. `"Let"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum NodeKind {
+    /// Function definition; the label is its name and parameters.
+    Fn,
+    /// Statement that does nothing, such as an empty `;`.
+    Noop,
+    /// `if`, with the condition and branches as children.
+    If,
+    /// `switch`, with the matched value and cases as children.
+    Switch,
+    /// `loop`.
+    Loop,
+    /// `while` loop.
+    While,
+    /// `do ... while` loop.
+    DoWhile,
+    /// `do ... until` loop.
+    DoUntil,
+    /// `for` loop; the label is the loop variable, or `(variable, counter)`.
+    For,
+    /// `let`; the label is the variable name.
+    Let,
+    /// `const`; the label is the constant name.
+    Const,
+    /// Assignment; the label is the operator, e.g. `=` or `+=`.
+    Assignment,
+    /// Operator call; the label is the operator, e.g. `+`.
+    Operator,
+    /// Function call; the label is the name, qualified with its module if any.
+    FnCall,
+    /// Method call; the label is the method name.
+    MethodCall,
+    /// `{}` block of statements.
+    Block,
+    /// `try ... catch`.
+    TryCatch,
+    /// `break`.
+    Break,
+    /// `continue`.
+    Continue,
+    /// `return`.
+    Return,
+    /// `throw`.
+    Throw,
+    /// `import`; the label is the alias, if any.
+    Import,
+    /// `export`; the label is the exported name.
+    Export,
+    /// Variables captured by a closure; the label lists their names.
+    Share,
+    /// `()` literal; the value is in [`AstNode::value`], as are those of the
+    /// other literals.
+    Unit,
+    /// `true` or `false`.
+    Bool,
+    /// Integer literal.
+    Integer,
+    /// Floating-point literal.
+    Float,
+    /// Character literal.
+    Char,
+    /// String literal.
+    String,
+    /// Constant computed by the optimizer; the value is in [`AstNode::value`].
+    Constant,
+    /// Template string with `${}` interpolations.
+    InterpolatedString,
+    /// Array literal.
+    Array,
+    /// Object map literal; the label lists its keys as `#{a, b}`.
+    Map,
+    /// Variable; the label is its name, qualified with its module if any.
+    Variable,
+    /// `this`.
+    This,
+    /// Property of a `Dot`; the label is its name.
+    Property,
+    /// `a.b` access.
+    Dot,
+    /// `a[b]` access.
+    Index,
+    /// `&&`.
+    And,
+    /// `||`.
+    Or,
+    /// `??`.
+    Coalesce,
+    /// Custom syntax; the label is its tokens.
+    Custom,
+    /// Node of a kind added in a Rhai version newer than this crate knows.
+    /// Not produced with the pinned Rhai version.
+    Unknown,
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Value of a literal or constant node, written to JSON as
+/// `{"type": "int", "value": 3}`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum Literal {
+    /// `()`, written without a `value`.
+    Unit,
+    /// `true` or `false`.
+    Bool(bool),
+    /// Integer.
+    Int(i64),
+    /// Floating-point number.
+    Float(f64),
+    /// Character, written as a one-character string.
+    Char(char),
+    /// String.
+    String(String),
+    /// Array constant, with its items.
+    Array(Vec<Literal>),
+    /// Object map constant, with its entries by key.
+    Map(BTreeMap<String, Literal>),
+    /// Constant of any other type, given by its type name.
+    Other(String),
+}
+
+impl Literal {
+    /// The literal holding `value`.
+    fn from_dynamic(value: &Dynamic) -> Self {
+        if value.is_unit() {
+            Literal::Unit
+        } else if let Ok(b) = value.as_bool() {
+            Literal::Bool(b)
+        } else if let Ok(i) = value.as_int() {
+            Literal::Int(i)
+        } else if let Ok(f) = value.as_float() {
+            Literal::Float(f)
+        } else if let Ok(c) = value.as_char() {
+            Literal::Char(c)
+        } else if let Ok(s) = value.as_immutable_string_ref() {
+            Literal::String(s.to_string())
+        } else if let Some(array) = value.read_lock::<Array>() {
+            Literal::Array(array.iter().map(Literal::from_dynamic).collect())
+        } else if let Some(map) = value.read_lock::<Map>() {
+            Literal::Map(
+                map.iter()
+                    .map(|(key, item)| (key.to_string(), Literal::from_dynamic(item)))
+                    .collect(),
+            )
+        } else {
+            Literal::Other(value.type_name().to_string())
+        }
+    }
+}
+
+/// The literal as it would be written in a script.
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Unit => write!(f, "()"),
+            Literal::Bool(b) => write!(f, "{b}"),
+            Literal::Int(i) => write!(f, "{i}"),
+            Literal::Float(x) => write!(f, "{x:?}"),
+            Literal::Char(c) => write!(f, "{c:?}"),
+            Literal::String(s) => write!(f, "{s:?}"),
+            Literal::Array(items) => {
+                let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Literal::Map(entries) => {
+                let entries: Vec<String> =
+                    entries.iter().map(|(k, v)| format!("{k}: {v}")).collect();
+                write!(f, "#{{{}}}", entries.join(", "))
+            }
+            Literal::Other(type_name) => write!(f, "<{type_name}>"),
+        }
+    }
+}
+
+/// A statement, expression or function definition of a script.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AstNode {
+    /// Node kind.
+    pub kind: NodeKind,
+    /// Name or operator of the node, if it has one; see [`NodeKind`].
+    pub label: Option<String>,
+    /// Value of a literal or constant.
+    pub value: Option<Literal>,
+    /// Line the node starts at, starting at 1.
+    pub line: Option<usize>,
+    /// Column the node starts at, starting at 1.
+    pub column: Option<usize>,
+    /// Nested statements and expressions, in source order.
+    pub children: Vec<AstNode>,
+}
+
+impl AstNode {
+    fn new(kind: NodeKind, label: Option<String>, pos: Position) -> Self {
+        Self {
+            kind,
+            label,
+            value: None,
+            line: pos.line(),
+            column: pos.position(),
+            children: Vec::new(),
+        }
+    }
+
+    fn literal(kind: NodeKind, value: Literal, pos: Position) -> Self {
+        Self {
+            value: Some(value),
+            ..Self::new(kind, None, pos)
+        }
+    }
+
+    /// The kind followed by the label or value, e.g. `Let total` or `Integer 3`.
+    pub fn summary(&self) -> String {
+        match (&self.label, &self.value) {
+            (Some(label), _) => format!("{} {label}", self.kind),
+            (None, Some(value)) => format!("{} {value}", self.kind),
+            (None, None) => self.kind.to_string(),
+        }
+    }
+}
+
+/// Build the tree of the top-level statements and function definitions of
+/// `ast`, in source order.
+///
+/// Statements that only wrap an expression are left out, so the expression
+/// takes their place.
+pub fn syntax_tree(ast: &AST) -> Vec<AstNode> {
+    let mut roots = Vec::new();
+    for stmt in ast.statements() {
+        add_stmt(&mut roots, stmt);
+    }
+    for f in ast.iter_fn_def() {
+        let label = format!("{}({})", f.name, f.params.join(", "));
+        let mut node = AstNode::new(NodeKind::Fn, Some(label), f.body.position());
+        node.children = body_tree(f);
+        roots.push(node);
+    }
+    roots.sort_by_key(|n| (n.line, n.column));
+    roots
+}
+
+/// Render `tree` as one line per node with its [`AstNode::summary`], indented by
+/// depth and without positions, so that trees can be compared line by line.
+pub fn tree_outline(tree: &[AstNode]) -> String {
+    fn write(out: &mut String, nodes: &[AstNode], depth: usize) {
+        for node in nodes {
+            out.push_str(&"  ".repeat(depth));
+            out.push_str(&node.summary());
+            out.push('\n');
+            write(out, &node.children, depth + 1);
+        }
+    }
+    let mut out = String::new();
+    write(&mut out, tree, 0);
+    out
+}
+
+/// Tree of the statements in the body of `f`.
+fn body_tree(f: &ScriptFuncDef) -> Vec<AstNode> {
+    let mut nodes = Vec::new();
+    for stmt in f.body.statements() {
+        add_stmt(&mut nodes, stmt);
+    }
+    nodes
+}
+
+/// A function defined by a script, in an [`AstExport`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FnDef {
+    /// Function name.
+    pub name: String,
+    /// Parameter names in order.
+    pub params: Vec<String>,
+    /// Whether the function is `private` to its module.
+    pub private: bool,
+    /// Doc comment lines, including the leading `///`.
+    pub doc_comments: Vec<String>,
+    /// Line of the opening brace of the body, starting at 1.
+    pub line: Option<usize>,
+    /// Column of the opening brace of the body, starting at 1.
+    pub column: Option<usize>,
+    /// Statements of the body.
+    pub body: Vec<AstNode>,
+}
+
+/// An `import` statement, in an [`AstExport`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ImportDef {
+    /// Module path, if given as a string literal.
+    pub path: Option<String>,
+    /// Name the module is bound to, if any.
+    pub alias: Option<String>,
+    /// Line of the statement, starting at 1.
+    pub line: Option<usize>,
+    /// Column of the statement, starting at 1.
+    pub column: Option<usize>,
+}
+
+/// Serializable description of a compiled script: its top-level statements,
+/// function definitions and imports, with positions.
+///
+/// The JSON layout is stable within an [`AST_JSON_VERSION`], so exports can be
+/// consumed by external tools and compared between versions of a script:
+///
+/// ```text
+/// {
+///   "version": 1,
+///   "statements": [Node],
+///   "functions": [{ "name": string, "params": [string], "private": bool,
+///                   "doc_comments": [string], "line": int?, "column": int?,
+///                   "body": [Node] }],
+///   "imports": [{ "path": string?, "alias": string?, "line": int?, "column": int? }]
+/// }
+///
+/// Node = { "kind": NodeKind, "label": string?, "value": Literal?,
+///          "line": int?, "column": int?, "children": [Node] }
+///
+/// Literal = { "type": "unit" }
+///         | { "type": "bool", "value": bool }
+///         | { "type": "int", "value": int }
+///         | { "type": "float", "value": number }
+///         | { "type": "char", "value": string }
+///         | { "type": "string", "value": string }
+///         | { "type": "array", "value": [Literal] }
+///         | { "type": "map", "value": { string: Literal } }
+///         | { "type": "other", "value": type name }
+/// ```
+///
+/// `kind` is one of the names of [`NodeKind`], `label` is described there,
+/// and `value` is set for literal and constant nodes only. Optional fields are
+/// written as `null`; lines and columns start at 1.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AstExport {
+    /// Always [`AST_JSON_VERSION`].
+    pub version: u32,
+    /// Top-level statements in source order.
+    pub statements: Vec<AstNode>,
+    /// Functions defined by the script, in source order.
+    pub functions: Vec<FnDef>,
+    /// Every `import` statement, including nested ones, in source order.
+    pub imports: Vec<ImportDef>,
+}
+
+impl AstExport {
+    /// Describe `ast`.
+    pub fn new(ast: &AST) -> Self {
+        let mut statements = Vec::new();
+        for stmt in ast.statements() {
+            add_stmt(&mut statements, stmt);
+        }
+        let mut functions: Vec<FnDef> = ast
+            .iter_fn_def()
+            .map(|f| {
+                let pos = f.body.position();
+                FnDef {
+                    name: f.name.to_string(),
+                    params: f.params.iter().map(ToString::to_string).collect(),
+                    private: f.access == FnAccess::Private,
+                    doc_comments: f.comments.iter().map(ToString::to_string).collect(),
+                    line: pos.line(),
+                    column: pos.position(),
+                    body: body_tree(f),
+                }
+            })
+            .collect();
+        functions.sort_by_key(|f| (f.line, f.column));
+        let mut imports = Vec::new();
+        ast.walk(&mut |path: &[ASTNode]| {
+            if let Some(ASTNode::Stmt(Stmt::Import(x, pos))) = path.last() {
+                let (expr, alias) = &**x;
+                imports.push(ImportDef {
+                    path: match expr {
+                        Expr::StringConstant(path, _) => Some(path.to_string()),
+                        _ => None,
+                    },
+                    alias: Some(alias.name.to_string()).filter(|a| !a.is_empty()),
+                    line: pos.line(),
+                    column: pos.position(),
+                });
+            }
+            true
+        });
+        imports.sort_by_key(|i| (i.line, i.column));
+        Self {
+            version: AST_JSON_VERSION,
+            statements,
+            functions,
+            imports,
+        }
+    }
+
+    /// The export as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Append the tree of `stmt` to `nodes`.
+fn add_stmt(nodes: &mut Vec<AstNode>, stmt: &Stmt) {
+    // Nodes that are still being filled, with the length of their walk path.
+    let mut open: Vec<(usize, AstNode)> = Vec::new();
+    stmt.walk(&mut Vec::new(), &mut |path: &[ASTNode]| {
+        let depth = path.len();
+        while open.last().is_some_and(|(d, _)| *d >= depth) {
+            close(&mut open, nodes);
+        }
+        if let Some(node) = path.last().and_then(describe) {
+            open.push((depth, node));
+        }
+        true
+    });
+    while !open.is_empty() {
+        close(&mut open, nodes);
+    }
+}
+
+/// Move the innermost open node into its parent, or into `nodes` at the top.
+fn close(open: &mut Vec<(usize, AstNode)>, nodes: &mut Vec<AstNode>) {
+    if let Some((_, node)) = open.pop() {
+        match open.last_mut() {
+            Some((_, parent)) => parent.children.push(node),
+            None => nodes.push(node),
+        }
+    }
+}
+
+/// The tree node for a walked statement or expression, or `None` to leave it out.
+fn describe(node: &ASTNode) -> Option<AstNode> {
+    match node {
+        ASTNode::Stmt(stmt) => describe_stmt(stmt),
+        ASTNode::Expr(expr) => Some(describe_expr(expr)),
+        _ => None,
+    }
+}
+
+fn describe_stmt(stmt: &Stmt) -> Option<AstNode> {
+    use NodeKind as K;
+    let (kind, label) = match stmt {
+        Stmt::Expr(_) => return None,
+        Stmt::Noop(_) => (K::Noop, None),
+        Stmt::If(..) => (K::If, None),
+        Stmt::Switch(..) => (K::Switch, None),
+        Stmt::While(x, _) if matches!(x.expr, Expr::Unit(_)) => (K::Loop, None),
+        Stmt::While(..) => (K::While, None),
+        Stmt::Do(_, flags, _) if flags.intersects(ASTFlags::NEGATED) => (K::DoUntil, None),
+        Stmt::Do(..) => (K::DoWhile, None),
+        Stmt::For(x, _) => {
+            let (var, counter, _) = &**x;
+            let label = match counter {
+                Some(counter) => format!("({}, {})", var.name, counter.name),
+                None => var.name.to_string(),
+            };
+            (K::For, Some(label))
+        }
+        Stmt::Var(x, flags, _) => {
+            let kind = if flags.intersects(ASTFlags::CONSTANT) {
+                K::Const
+            } else {
+                K::Let
+            };
+            (kind, Some(x.0.name.to_string()))
+        }
+        Stmt::Assignment(x) => {
+            let op = x.0.get_op_assignment_info().map_or("=", |info| info.3);
+            (K::Assignment, Some(op.to_string()))
+        }
+        Stmt::FnCall(f, _) if f.op_token.is_some() => (K::Operator, Some(f.name.to_string())),
+        Stmt::FnCall(f, _) => (K::FnCall, Some(call_name(&f.namespace, &f.name))),
+        Stmt::Block(_) => (K::Block, None),
+        Stmt::TryCatch(..) => (K::TryCatch, None),
+        Stmt::BreakLoop(_, flags, _) if flags.intersects(ASTFlags::BREAK) => (K::Break, None),
+        Stmt::BreakLoop(..) => (K::Continue, None),
+        Stmt::Return(_, flags, _) if flags.intersects(ASTFlags::BREAK) => (K::Throw, None),
+        Stmt::Return(..) => (K::Return, None),
+        Stmt::Import(x, _) => (
+            K::Import,
+            Some(x.1.name.to_string()).filter(|a| !a.is_empty()),
+        ),
+        Stmt::Export(x, _) => (K::Export, Some(x.1.name.to_string())),
+        Stmt::Share(x) => {
+            let names: Vec<&str> = x.iter().map(|(v, _)| v.name.as_str()).collect();
+            (K::Share, Some(names.join(", ")))
+        }
+        _ => (K::Unknown, None),
+    };
+    let pos = match stmt {
+        Stmt::FnCall(f, _) if !f.namespace.is_empty() => f.namespace.position(),
+        _ => stmt.position(),
+    };
+    Some(AstNode::new(kind, label, pos))
+}
+
+fn describe_expr(expr: &Expr) -> AstNode {
+    use NodeKind as K;
+    let pos = expr.start_position();
+    let literal = match expr {
+        Expr::DynamicConstant(value, _) => Some((K::Constant, Literal::from_dynamic(value))),
+        Expr::BoolConstant(value, _) => Some((K::Bool, Literal::Bool(*value))),
+        Expr::IntegerConstant(value, _) => Some((K::Integer, Literal::Int(*value))),
+        Expr::FloatConstant(value, _) => Some((K::Float, Literal::Float(**value))),
+        Expr::CharConstant(value, _) => Some((K::Char, Literal::Char(*value))),
+        Expr::StringConstant(value, _) => Some((K::String, Literal::String(value.to_string()))),
+        Expr::Unit(_) => Some((K::Unit, Literal::Unit)),
+        _ => None,
+    };
+    if let Some((kind, value)) = literal {
+        return AstNode::literal(kind, value, pos);
+    }
+    let (kind, label) = match expr {
+        Expr::InterpolatedString(..) => (K::InterpolatedString, None),
+        Expr::Array(..) => (K::Array, None),
+        Expr::Map(x, _) => {
+            let keys: Vec<&str> = x.0.iter().map(|(key, _)| key.name.as_str()).collect();
+            (K::Map, Some(format!("#{{{}}}", keys.join(", "))))
+        }
+        Expr::Variable(x, ..) => (K::Variable, Some(call_name(&x.2, &x.1))),
+        Expr::ThisPtr(_) => (K::This, None),
+        Expr::Property(x, _) => (K::Property, Some(x.2.to_string())),
+        Expr::MethodCall(f, _) => (K::MethodCall, Some(f.name.to_string())),
+        Expr::Stmt(_) => (K::Block, None),
+        Expr::FnCall(f, _) if f.op_token.is_some() => (K::Operator, Some(f.name.to_string())),
+        Expr::FnCall(f, _) => (K::FnCall, Some(call_name(&f.namespace, &f.name))),
+        Expr::Dot(..) => (K::Dot, None),
+        Expr::Index(..) => (K::Index, None),
+        Expr::And(..) => (K::And, None),
+        Expr::Or(..) => (K::Or, None),
+        Expr::Coalesce(..) => (K::Coalesce, None),
+        Expr::Custom(x, _) => (K::Custom, Some(x.tokens.join(" "))),
+        _ => (K::Unknown, None),
+    };
+    // Qualified calls start at their namespace rather than the function name.
+    let pos = match expr {
+        Expr::FnCall(f, _) if !f.namespace.is_empty() => f.namespace.position(),
+        _ => pos,
+    };
+    AstNode::new(kind, label, pos)
+}
+
+/// `name` qualified with its module `namespace`, if any.
+fn call_name(namespace: &rhai::Namespace, name: &str) -> String {
+    if namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{namespace}::{name}")
+    }
+}
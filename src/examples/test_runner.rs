@@ -0,0 +1,157 @@
+//! Discovery and execution of `test_*` functions defined in example scripts.
+
+use super::{ErrorKind, Example, ScriptError, script_imports};
+use crate::host::HostEngine;
+use rhai::{CallFnOptions, Dynamic, EvalAltResult, Scope};
+use std::time::{Duration, Instant};
+
+/// Prefix that marks a script function as a test.
+pub const TEST_PREFIX: &str = "test_";
+
+/// How a single test function finished.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestOutcome {
+    /// The function returned normally.
+    Passed,
+    /// The function threw, e.g. through a failed assertion.
+    Failed(ScriptError),
+    /// The function stopped for any other reason, such as a missing function or a limit.
+    Error(ScriptError),
+}
+
+/// Result of running one test function.
+#[derive(Clone, Debug)]
+pub struct TestCase {
+    /// Name of the test function.
+    pub name: String,
+    /// Module the test was defined in, relative to the example script, if not the script itself.
+    pub module: Option<String>,
+    /// How the test finished.
+    pub outcome: TestOutcome,
+    /// Time spent running the test.
+    pub duration: Duration,
+    /// Output printed by the test.
+    pub stdout: String,
+}
+
+/// Results of running every test of an example.
+#[derive(Clone, Debug)]
+pub struct TestReport {
+    /// Id of the example whose tests were run.
+    pub example_id: String,
+    /// One entry per discovered test function.
+    pub cases: Vec<TestCase>,
+    /// Errors that prevented tests from being discovered, e.g. a script that does not compile.
+    pub errors: Vec<ScriptError>,
+}
+
+impl TestReport {
+    /// Number of tests that passed.
+    pub fn passed(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|c| c.outcome == TestOutcome::Passed)
+            .count()
+    }
+
+    /// Whether every test passed and nothing failed to compile.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty() && self.passed() == self.cases.len()
+    }
+}
+
+/// Run the tests defined in `script` and in the modules it imports.
+pub(super) fn run_tests(example: &Example, script: &str) -> TestReport {
+    let host = example.host_engine();
+    let mut report = TestReport {
+        example_id: example.id.clone(),
+        cases: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    run_source(&host, &mut report, None, script);
+
+    for import in script_imports(script) {
+        let mut file = import.path.clone();
+        if std::path::Path::new(&file).extension().is_none() {
+            file.push_str(".rhai");
+        }
+        let path = example.resolve_file(&file).unwrap_or_else(|| file.into());
+        match std::fs::read_to_string(&path) {
+            Ok(text) => run_source(&host, &mut report, Some(&import.path), &text),
+            Err(e) => report.errors.push(ScriptError {
+                kind: ErrorKind::Host,
+                message: format!("cannot read module '{}': {e}", import.path),
+                line: None,
+                column: None,
+                source: Some(import.path.clone()),
+                call_stack: Vec::new(),
+                example_id: example.id.clone(),
+            }),
+        }
+    }
+
+    report
+}
+
+/// Run every test function of one script or module.
+///
+/// Each test is called on a fresh scope. Only the `import` statements of the
+/// source are evaluated beforehand, so top-level code does not run.
+fn run_source(host: &HostEngine, report: &mut TestReport, module: Option<&str>, source: &str) {
+    let engine = host.engine();
+    let id = report.example_id.clone();
+    let compiled = engine.compile(source).map_err(Box::from).and_then(|ast| {
+        let setup: String = script_imports(source)
+            .iter()
+            .map(|i| i.to_statement())
+            .collect();
+        Ok(engine.compile(setup)?.merge(&ast.clone_functions_only()))
+    });
+    let mut ast = match compiled {
+        Ok(ast) => ast,
+        Err(e) => {
+            let mut err = ScriptError::from_eval(&id, &e);
+            err.source = module.map(str::to_string);
+            report.errors.push(err);
+            return;
+        }
+    };
+    if let Some(module) = module {
+        ast.set_source(module);
+    }
+
+    let mut names: Vec<String> = ast
+        .iter_functions()
+        .filter(|f| f.name.starts_with(TEST_PREFIX) && f.params.is_empty())
+        .map(|f| f.name.to_string())
+        .collect();
+    names.sort();
+
+    for name in names {
+        host.take_output();
+        let started = Instant::now();
+        let mut scope = Scope::new();
+        let options = CallFnOptions::new().eval_ast(true).rewind_scope(true);
+        let result = engine.call_fn_with_options::<Dynamic>(options, &mut scope, &ast, &name, ());
+        let duration = started.elapsed();
+        let outcome = match result {
+            Ok(_) => TestOutcome::Passed,
+            Err(e) => {
+                let err = ScriptError::from_eval(&id, &e);
+                if matches!(e.unwrap_inner(), EvalAltResult::ErrorRuntime(..)) {
+                    TestOutcome::Failed(err)
+                } else {
+                    TestOutcome::Error(err)
+                }
+            }
+        };
+        report.cases.push(TestCase {
+            name,
+            module: module.map(str::to_string),
+            outcome,
+            duration,
+            stdout: host.take_output(),
+        });
+    }
+}
@@ -0,0 +1,87 @@
+//! Snapshots of the variables in a script's scope, for display.
+
+use rhai::{Array, Dynamic, Map, Scope};
+
+/// Children listed for an array or map at most; the rest are summarised.
+const MAX_CHILDREN: usize = 500;
+
+/// A variable, array element or map entry with its type and value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variable {
+    /// Variable name, `[index]` of an array element or key of a map entry.
+    pub name: String,
+    /// Rhai type name, e.g. `i64`, `string`, `array` or `Point`.
+    pub type_name: String,
+    /// The value, or the number of items of an array or map.
+    pub value: String,
+    /// Whether the variable was declared with `const`.
+    pub constant: bool,
+    /// Elements of an array or entries of a map, in order.
+    pub children: Vec<Variable>,
+}
+
+impl Variable {
+    /// Describe `value` under `name`, expanding arrays and maps.
+    pub fn new(name: impl Into<String>, value: &Dynamic) -> Self {
+        let value = value.flatten_clone();
+        // Custom types are named by their Rust path.
+        let type_name = value.type_name();
+        let type_name = type_name.rsplit("::").next().unwrap_or(type_name);
+        let (summary, children) = if let Some(array) = value.read_lock::<Array>() {
+            let children = array
+                .iter()
+                .take(MAX_CHILDREN)
+                .enumerate()
+                .map(|(i, item)| Variable::new(format!("[{i}]"), item))
+                .collect();
+            (items(array.len(), "item"), children)
+        } else if let Some(map) = value.read_lock::<Map>() {
+            let children = map
+                .iter()
+                .take(MAX_CHILDREN)
+                .map(|(key, item)| Variable::new(key.as_str(), item))
+                .collect();
+            (items(map.len(), "entry"), children)
+        } else if value.is_string() {
+            (format!("{value:?}"), Vec::new())
+        } else {
+            (value.to_string(), Vec::new())
+        };
+        Self {
+            name: name.into(),
+            type_name: type_name.to_string(),
+            value: summary,
+            constant: false,
+            children,
+        }
+    }
+}
+
+/// `count` followed by `noun`, pluralised, with a note when only the first
+/// [`MAX_CHILDREN`] are listed.
+fn items(count: usize, noun: &str) -> String {
+    let mut text = match (count, noun) {
+        (1, _) => format!("1 {noun}"),
+        (_, "entry") => format!("{count} entries"),
+        _ => format!("{count} {noun}s"),
+    };
+    if count > MAX_CHILDREN {
+        text.push_str(&format!(", first {MAX_CHILDREN} shown"));
+    }
+    text
+}
+
+/// The variables of `scope` in declaration order.
+///
+/// Variables shadowed by a later one of the same name are left out.
+pub fn scope_variables(scope: &Scope) -> Vec<Variable> {
+    let mut variables: Vec<Variable> = Vec::new();
+    for (name, constant, value) in scope.iter() {
+        variables.retain(|v| v.name != name);
+        variables.push(Variable {
+            constant,
+            ..Variable::new(name, &value)
+        });
+    }
+    variables
+}
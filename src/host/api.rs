@@ -0,0 +1,175 @@
+//! Descriptions of the functions and types available to scripts, read from
+//! the engine's metadata.
+
+use rhai::{AST, Engine};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Where a function available to scripts comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FnOrigin {
+    /// Defined in the script itself.
+    Script,
+    /// Registered by [`register_host_api`](super::register_host_api).
+    Host,
+    /// Part of Rhai's standard packages.
+    #[default]
+    Builtin,
+}
+
+/// A parameter of a [`FnInfo`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct FnParam {
+    /// Parameter name, if the function declares one.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Parameter type, if known.
+    #[serde(default, rename = "type")]
+    pub typ: Option<String>,
+}
+
+/// A function scripts can call, with its signature and documentation.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FnInfo {
+    /// Name the function is called by.
+    pub name: String,
+    /// Parameters in order.
+    #[serde(default)]
+    pub params: Vec<FnParam>,
+    /// Return type, empty if the function returns nothing.
+    #[serde(default)]
+    pub return_type: String,
+    /// Full signature, e.g. `rand_int(min: i64, max: i64) -> i64`.
+    pub signature: String,
+    /// Doc comment lines, including the leading `///`.
+    #[serde(default)]
+    pub doc_comments: Vec<String>,
+    /// Where the function comes from.
+    #[serde(skip)]
+    pub origin: FnOrigin,
+    #[serde(rename = "type")]
+    kind: String,
+    full_hash: u64,
+    #[serde(default)]
+    is_anonymous: bool,
+    #[serde(default)]
+    access: String,
+}
+
+impl FnInfo {
+    /// Parameter list as written in the signature, without the parentheses.
+    pub fn param_list(&self) -> String {
+        self.params
+            .iter()
+            .map(|p| match (&p.name, &p.typ) {
+                (Some(name), Some(typ)) => format!("{name}: {typ}"),
+                (Some(name), None) => name.clone(),
+                (None, Some(typ)) => typ.clone(),
+                (None, None) => "_".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Documentation with the comment markers removed.
+    pub fn doc(&self) -> String {
+        strip_comments(&self.doc_comments)
+    }
+
+    /// Whether the function is a `private` script function, which cannot be
+    /// called from outside its module.
+    pub fn is_private(&self) -> bool {
+        self.access == "private"
+    }
+
+    /// Whether the function is an operator such as `+` or `==`.
+    pub fn is_operator(&self) -> bool {
+        !self
+            .name
+            .starts_with(|c: char| c.is_alphabetic() || c == '_')
+    }
+}
+
+/// A custom type registered on the engine.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeInfo {
+    /// Name the type is shown as to scripts, e.g. `Point`.
+    #[serde(rename = "displayName")]
+    pub name: String,
+    /// Doc comment lines, including the leading `///`.
+    #[serde(default)]
+    pub doc_comments: Vec<String>,
+}
+
+impl TypeInfo {
+    /// Documentation with the comment markers removed.
+    pub fn doc(&self) -> String {
+        strip_comments(&self.doc_comments)
+    }
+}
+
+fn strip_comments(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            let line = line.trim_start_matches("///").trim_start_matches("/**");
+            line.trim_end_matches("*/").trim()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+    #[serde(default)]
+    functions: Vec<FnInfo>,
+    #[serde(default)]
+    custom_types: Vec<TypeInfo>,
+}
+
+fn parse(json: serde_json::Result<String>) -> Metadata {
+    json.ok()
+        .and_then(|json| serde_json::from_str::<Metadata>(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Describe every function `engine` offers to scripts, plus the functions
+/// defined in `ast` if given, sorted by name.
+pub fn functions(engine: &Engine, ast: Option<&AST>) -> Vec<FnInfo> {
+    let host: HashSet<u64> = parse(engine.gen_fn_metadata_to_json(false))
+        .functions
+        .iter()
+        .map(|f| f.full_hash)
+        .collect();
+    let all = match ast {
+        Some(ast) => engine.gen_fn_metadata_with_ast_to_json(ast, true),
+        None => engine.gen_fn_metadata_to_json(true),
+    };
+    parse(all)
+        .functions
+        .into_iter()
+        .filter(|f| !f.is_anonymous)
+        .map(|mut f| {
+            f.origin = if f.kind == "script" {
+                FnOrigin::Script
+            } else if host.contains(&f.full_hash) {
+                FnOrigin::Host
+            } else {
+                FnOrigin::Builtin
+            };
+            f
+        })
+        .collect()
+}
+
+/// Describe the custom types registered on `engine`, sorted by name.
+pub fn types(engine: &Engine) -> Vec<TypeInfo> {
+    let mut types = parse(engine.gen_fn_metadata_to_json(false)).custom_types;
+    types.sort_by(|a, b| a.name.cmp(&b.name));
+    types
+}
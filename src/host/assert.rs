@@ -0,0 +1,174 @@
+//! Assertion functions for test-style example scripts.
+//!
+//! Failed assertions raise catchable Rhai runtime errors at the call site
+//! instead of panicking the host.
+
+use rhai::{Dynamic, Engine, EvalAltResult, FnPtr, FuncRegistration, NativeCallContext};
+
+type AssertResult = Result<(), Box<EvalAltResult>>;
+
+/// Register `assert`, `assert_eq`, `assert_ne`, `assert_true`, `assert_false`
+/// and `assert_throws` on `engine`.
+pub fn register(engine: &mut Engine) {
+    FuncRegistration::new("assert")
+        .with_params_info(["condition: bool"])
+        .with_comments(["/// Fail with \"assertion failed\" unless `condition` is true."])
+        .register_into_engine(engine, |ctx: NativeCallContext, cond: bool| {
+            check(&ctx, cond, None, "assertion failed")
+        });
+    FuncRegistration::new("assert")
+        .with_params_info(["condition: bool", "message: &str"])
+        .with_comments(["/// Fail with `message` unless `condition` is true."])
+        .register_into_engine(engine, |ctx: NativeCallContext, cond: bool, msg: &str| {
+            check(&ctx, cond, Some(msg), "assertion failed")
+        });
+    FuncRegistration::new("assert_true")
+        .with_params_info(["condition: bool"])
+        .with_comments(["/// Fail unless `condition` is true."])
+        .register_into_engine(engine, |ctx: NativeCallContext, cond: bool| {
+            check(&ctx, cond, None, "expected true, got false")
+        });
+    FuncRegistration::new("assert_true")
+        .with_params_info(["condition: bool", "message: &str"])
+        .with_comments(["/// Fail with `message` unless `condition` is true."])
+        .register_into_engine(engine, |ctx: NativeCallContext, cond: bool, msg: &str| {
+            check(&ctx, cond, Some(msg), "expected true, got false")
+        });
+    FuncRegistration::new("assert_false")
+        .with_params_info(["condition: bool"])
+        .with_comments(["/// Fail unless `condition` is false."])
+        .register_into_engine(engine, |ctx: NativeCallContext, cond: bool| {
+            check(&ctx, !cond, None, "expected false, got true")
+        });
+    FuncRegistration::new("assert_false")
+        .with_params_info(["condition: bool", "message: &str"])
+        .with_comments(["/// Fail with `message` unless `condition` is false."])
+        .register_into_engine(engine, |ctx: NativeCallContext, cond: bool, msg: &str| {
+            check(&ctx, !cond, Some(msg), "expected false, got true")
+        });
+    FuncRegistration::new("assert_eq")
+        .with_params_info(["actual: Dynamic", "expected: Dynamic"])
+        .with_comments(["/// Fail unless `actual` equals `expected`."])
+        .register_into_engine(
+            engine,
+            |ctx: NativeCallContext, actual: Dynamic, expected: Dynamic| {
+                assert_eq(&ctx, actual, expected, None)
+            },
+        );
+    FuncRegistration::new("assert_eq")
+        .with_params_info(["actual: Dynamic", "expected: Dynamic", "message: &str"])
+        .with_comments(["/// Fail with `message` unless `actual` equals `expected`."])
+        .register_into_engine(
+            engine,
+            |ctx: NativeCallContext, actual: Dynamic, expected: Dynamic, msg: &str| {
+                assert_eq(&ctx, actual, expected, Some(msg))
+            },
+        );
+    FuncRegistration::new("assert_ne")
+        .with_params_info(["actual: Dynamic", "unexpected: Dynamic"])
+        .with_comments(["/// Fail if `actual` equals `unexpected`."])
+        .register_into_engine(
+            engine,
+            |ctx: NativeCallContext, actual: Dynamic, unexpected: Dynamic| {
+                assert_ne(&ctx, actual, unexpected, None)
+            },
+        );
+    FuncRegistration::new("assert_ne")
+        .with_params_info(["actual: Dynamic", "unexpected: Dynamic", "message: &str"])
+        .with_comments(["/// Fail with `message` if `actual` equals `unexpected`."])
+        .register_into_engine(
+            engine,
+            |ctx: NativeCallContext, actual: Dynamic, unexpected: Dynamic, msg: &str| {
+                assert_ne(&ctx, actual, unexpected, Some(msg))
+            },
+        );
+    FuncRegistration::new("assert_throws")
+        .with_params_info(["f: FnPtr", "Dynamic"])
+        .with_comments(["/// Call `f`, failing unless it throws, and return the thrown value."])
+        .register_into_engine(engine, |ctx: NativeCallContext, f: FnPtr| {
+            assert_throws(&ctx, f, None)
+        });
+    FuncRegistration::new("assert_throws")
+        .with_params_info(["f: FnPtr", "message: &str", "Dynamic"])
+        .with_comments([
+            "/// Call `f`, failing with `message` unless it throws, and return the",
+            "/// thrown value.",
+        ])
+        .register_into_engine(engine, |ctx: NativeCallContext, f: FnPtr, msg: &str| {
+            assert_throws(&ctx, f, Some(msg))
+        });
+}
+
+/// Build a runtime error located at the assertion's call site.
+fn failure(ctx: &NativeCallContext, msg: Option<&str>, detail: &str) -> Box<EvalAltResult> {
+    let text = match msg {
+        Some(msg) => format!("{msg}: {detail}"),
+        None => detail.to_string(),
+    };
+    EvalAltResult::ErrorRuntime(text.into(), ctx.call_position()).into()
+}
+
+fn check(ctx: &NativeCallContext, cond: bool, msg: Option<&str>, detail: &str) -> AssertResult {
+    if cond {
+        Ok(())
+    } else {
+        Err(failure(ctx, msg, detail))
+    }
+}
+
+/// Compare two values with the script's `==` operator.
+///
+/// Values of types without an `==` operator are compared by their debug form.
+fn values_equal(ctx: &NativeCallContext, a: &Dynamic, b: &Dynamic) -> bool {
+    if a.type_id() != b.type_id() {
+        return false;
+    }
+    ctx.call_native_fn::<bool>("==", (a.clone(), b.clone()))
+        .unwrap_or_else(|_| format!("{a:?}") == format!("{b:?}"))
+}
+
+fn assert_eq(
+    ctx: &NativeCallContext,
+    actual: Dynamic,
+    expected: Dynamic,
+    msg: Option<&str>,
+) -> AssertResult {
+    if values_equal(ctx, &actual, &expected) {
+        return Ok(());
+    }
+    let detail = format!("assert_eq failed: expected {expected:?}, actual {actual:?}");
+    Err(failure(ctx, msg, &detail))
+}
+
+fn assert_ne(
+    ctx: &NativeCallContext,
+    actual: Dynamic,
+    unexpected: Dynamic,
+    msg: Option<&str>,
+) -> AssertResult {
+    if !values_equal(ctx, &actual, &unexpected) {
+        return Ok(());
+    }
+    let detail =
+        format!("assert_ne failed: expected anything but {unexpected:?}, actual {actual:?}");
+    Err(failure(ctx, msg, &detail))
+}
+
+/// Call `f` and return the value it threw, failing if it returned normally.
+fn assert_throws(
+    ctx: &NativeCallContext,
+    f: FnPtr,
+    msg: Option<&str>,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    match f.call_within_context::<Dynamic>(ctx, ()) {
+        Ok(value) => {
+            let detail = format!("assert_throws failed: function returned {value:?}");
+            Err(failure(ctx, msg, &detail))
+        }
+        Err(err) if err.unwrap_inner().is_catchable() => match err.unwrap_inner() {
+            EvalAltResult::ErrorRuntime(value, ..) => Ok(value.clone()),
+            inner => Ok(inner.to_string().into()),
+        },
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,74 @@
+//! Capabilities that gate access to host functions with side effects.
+
+use rhai::{Dynamic, EvalAltResult, Map, Position};
+use serde::Deserialize;
+use std::fmt;
+
+/// A permission an example must be granted before it may call certain host functions.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Network access, required by `http_get`.
+    #[serde(rename = "net")]
+    Net,
+    /// Reading files from disk, required by `read_file`.
+    #[serde(rename = "fs.read")]
+    FsRead,
+    /// Blocking on the clock, required by `sleep_ms`.
+    #[serde(rename = "time")]
+    Time,
+    /// Random number generation, required by `rand_int`.
+    #[serde(rename = "random")]
+    Random,
+}
+
+impl Capability {
+    /// The name used for this capability in `manifest.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Net => "net",
+            Self::FsRead => "fs.read",
+            Self::Time => "time",
+            Self::Random => "random",
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Value of the `error` field of a missing capability error.
+const NOT_GRANTED: &str = "capability not granted";
+
+/// Build the host error raised when `function` is called without `capability`.
+///
+/// It is a runtime error, so scripts can catch it with `try`/`catch`. The
+/// caught value is a map such as `#{ error: "capability not granted",
+/// capability: "net", function: "http_get", message: "..." }`.
+pub(crate) fn not_granted(capability: Capability, function: &str) -> Box<EvalAltResult> {
+    let mut map = Map::new();
+    map.insert("error".into(), NOT_GRANTED.into());
+    map.insert("capability".into(), capability.name().into());
+    map.insert("function".into(), function.into());
+    map.insert(
+        "message".into(),
+        format!("`{function}` requires `{capability}`").into(),
+    );
+    EvalAltResult::ErrorRuntime(Dynamic::from_map(map), Position::NONE).into()
+}
+
+/// Describe `err` if it is a missing capability error from [`not_granted`],
+/// e.g. "capability not granted: `http_get` requires `net`".
+pub fn not_granted_message(err: &EvalAltResult) -> Option<String> {
+    let EvalAltResult::ErrorRuntime(value, _) = err else {
+        return None;
+    };
+    let map = value.read_lock::<Map>()?;
+    let field = |name: &str| map.get(name).and_then(|v| v.clone().into_string().ok());
+    if field("error")?.as_str() != NOT_GRANTED {
+        return None;
+    }
+    Some(format!("{NOT_GRANTED}: {}", field("message")?))
+}
@@ -0,0 +1,170 @@
+//! Resource limits applied to the host engine.
+
+use rhai::{Engine, EvalAltResult};
+use serde::Deserialize;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Termination token returned by the progress callback when the timeout expires.
+pub(crate) const TIMEOUT_TOKEN: &str = "timeout";
+
+/// Optional resource limits for a script run.
+///
+/// Every field is optional; a missing value leaves the engine default in place.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Limits {
+    /// Maximum number of operations the script may perform.
+    pub max_operations: Option<u64>,
+    /// Maximum depth of nested function calls.
+    pub max_call_depth: Option<usize>,
+    /// Maximum length of a string, in bytes.
+    pub max_string_size: Option<usize>,
+    /// Maximum number of elements in an array.
+    pub max_array_size: Option<usize>,
+    /// Maximum number of properties in an object map.
+    pub max_map_size: Option<usize>,
+    /// Maximum number of modules that may be imported.
+    pub max_modules: Option<usize>,
+    /// Wall-clock timeout in milliseconds.
+    pub timeout_ms: Option<u64>,
+}
+
+impl Limits {
+    /// Apply the size and count limits to an engine.
+    ///
+    /// The timeout is enforced separately through the engine's progress
+    /// callback, see [`timeout`](Self::timeout).
+    pub fn apply(&self, engine: &mut Engine) {
+        if let Some(n) = self.max_operations {
+            engine.set_max_operations(n);
+        }
+        if let Some(n) = self.max_call_depth {
+            engine.set_max_call_levels(n);
+        }
+        if let Some(n) = self.max_string_size {
+            engine.set_max_string_size(n);
+        }
+        if let Some(n) = self.max_array_size {
+            engine.set_max_array_size(n);
+        }
+        if let Some(n) = self.max_map_size {
+            engine.set_max_map_size(n);
+        }
+        if let Some(n) = self.max_modules {
+            engine.set_max_modules(n);
+        }
+    }
+
+    /// Wall-clock time each evaluation may take.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+}
+
+/// Wall-clock deadline of the current evaluation, shared between the
+/// engine's progress callback, which starts it, and the host functions that
+/// block, like `sleep_ms`.
+#[derive(Clone, Debug)]
+pub(crate) struct Deadline {
+    timeout: Option<Duration>,
+    base: Instant,
+    /// Nanoseconds after `base` at which the evaluation times out, or
+    /// `u64::MAX` before the first evaluation has started.
+    at: Arc<AtomicU64>,
+}
+
+impl Deadline {
+    pub(crate) fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            base: Instant::now(),
+            at: Arc::new(AtomicU64::new(u64::MAX)),
+        }
+    }
+
+    /// Whether there is a timeout to enforce at all.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.timeout.is_some()
+    }
+
+    /// Give an evaluation starting now the full timeout.
+    pub(crate) fn start(&self) {
+        if let Some(timeout) = self.timeout {
+            let at = (self.base.elapsed() + timeout).as_nanos();
+            self.at
+                .store(u64::try_from(at).unwrap_or(u64::MAX), Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the evaluation started last has run out of time.
+    pub(crate) fn has_passed(&self) -> bool {
+        let at = self.at.load(Ordering::Relaxed);
+        at != u64::MAX && self.base.elapsed().as_nanos() >= u128::from(at)
+    }
+}
+
+/// The kind of limit that stopped a script.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitKind {
+    /// `max_operations` was exceeded.
+    Operations,
+    /// `max_call_depth` was exceeded.
+    CallDepth,
+    /// `max_string_size` was exceeded.
+    StringSize,
+    /// `max_array_size` was exceeded.
+    ArraySize,
+    /// `max_map_size` was exceeded.
+    MapSize,
+    /// `max_modules` was exceeded.
+    Modules,
+    /// `timeout_ms` elapsed.
+    Timeout,
+}
+
+impl LimitKind {
+    /// Determine which limit, if any, caused the given error.
+    pub fn from_error(err: &EvalAltResult) -> Option<Self> {
+        match err.unwrap_inner() {
+            EvalAltResult::ErrorTooManyOperations(..) => Some(Self::Operations),
+            EvalAltResult::ErrorStackOverflow(..) => Some(Self::CallDepth),
+            EvalAltResult::ErrorTooManyModules(..) => Some(Self::Modules),
+            EvalAltResult::ErrorDataTooLarge(what, ..) => {
+                let what = what.to_ascii_lowercase();
+                if what.contains("string") {
+                    Some(Self::StringSize)
+                } else if what.contains("array") {
+                    Some(Self::ArraySize)
+                } else if what.contains("map") {
+                    Some(Self::MapSize)
+                } else {
+                    None
+                }
+            }
+            EvalAltResult::ErrorTerminated(token, ..)
+                if token.clone().into_string().ok().as_deref() == Some(TIMEOUT_TOKEN) =>
+            {
+                Some(Self::Timeout)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Operations => "max_operations",
+            Self::CallDepth => "max_call_depth",
+            Self::StringSize => "max_string_size",
+            Self::ArraySize => "max_array_size",
+            Self::MapSize => "max_map_size",
+            Self::Modules => "max_modules",
+            Self::Timeout => "timeout_ms",
+        };
+        f.write_str(name)
+    }
+}
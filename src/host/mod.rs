@@ -0,0 +1,492 @@
+//! Shared Rhai engine configuration used by every example run path.
+//!
+//! [`HostEngine`] owns the registration of the custom Rust types and helper
+//! functions exposed to scripts, the capture of `print`/`debug` output and the
+//! file based module resolver. New host functions only need to be added to
+//! [`register_host_api`]; functions with side effects are gated behind a
+//! [`Capability`].
+
+use rand::Rng;
+use rhai::debugger::{DebuggerCommand, DebuggerEvent};
+use rhai::module_resolvers::{FileModuleResolver, ModuleResolversCollection};
+use rhai::{
+    AST, ASTNode, CustomType, Dynamic, Engine, EvalAltResult, EvalContext, FuncRegistration,
+    OptimizationLevel, Position, TypeBuilder,
+};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+mod api;
+mod assert;
+mod capability;
+mod limits;
+
+pub use api::{FnInfo, FnOrigin, FnParam, TypeInfo};
+pub use capability::{Capability, not_granted_message};
+use limits::Deadline;
+pub use limits::{LimitKind, Limits};
+
+/// Termination token returned by the progress callback when a run is cancelled.
+const CANCEL_TOKEN: &str = "cancelled";
+
+/// Callback invoked with each line of `print`/`debug` output as it is produced.
+pub type OutputListener = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Callback invoked by Rhai's debugger on each event, deciding how the script
+/// goes on.
+pub type DebuggerCallback = Box<
+    dyn Fn(
+            EvalContext,
+            DebuggerEvent,
+            ASTNode,
+            Option<&str>,
+            Position,
+        ) -> Result<DebuggerCommand, Box<EvalAltResult>>
+        + Send
+        + Sync,
+>;
+
+/// Whether the given error was caused by cancelling the run.
+pub fn is_cancelled(err: &EvalAltResult) -> bool {
+    match err.unwrap_inner() {
+        EvalAltResult::ErrorTerminated(token, ..) => {
+            token.clone().into_string().ok().as_deref() == Some(CANCEL_TOKEN)
+        }
+        _ => false,
+    }
+}
+
+#[derive(Clone)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl CustomType for Point {
+    fn build(mut builder: TypeBuilder<Self>) {
+        builder
+            .with_name("Point")
+            .with_comments(&["/// A point with integer `x` and `y` coordinates."]);
+    }
+}
+
+impl Point {
+    fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    fn length(&mut self) -> f64 {
+        ((self.x * self.x + self.y * self.y) as f64).sqrt()
+    }
+}
+
+fn http_get(url: &str) -> Dynamic {
+    match reqwest::blocking::get(url) {
+        Ok(resp) => match resp.json::<Dynamic>() {
+            Ok(json) => json,
+            Err(e) => format!("Error parsing JSON: {e}").into(),
+        },
+        Err(e) => format!("Request error: {e}").into(),
+    }
+}
+
+fn to_json(value: Dynamic) -> String {
+    serde_json::to_string(&value).unwrap_or_default()
+}
+
+fn from_json(s: &str) -> Dynamic {
+    serde_json::from_str::<Dynamic>(s).unwrap_or(Dynamic::UNIT)
+}
+
+fn read_file(base_paths: &[PathBuf], path: &str) -> String {
+    // Use the first directory that has the file, or report the error for the last one.
+    let mut candidates = base_paths.iter().map(|base| base.join(path));
+    let path = candidates
+        .clone()
+        .find(|p| p.is_file())
+        .or_else(|| candidates.next_back())
+        .unwrap_or_else(|| PathBuf::from(path));
+    std::fs::read_to_string(path).unwrap_or_else(|e| format!("Error reading file: {e}"))
+}
+
+/// Longest stretch `sleep_ms` sleeps before checking whether to stop.
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
+/// Why a running script should stop early: a cancel flag and the wall-clock
+/// deadline, checked between operations and while sleeping.
+#[derive(Clone)]
+struct Interrupt {
+    cancel: Option<Arc<AtomicBool>>,
+    deadline: Deadline,
+}
+
+impl Interrupt {
+    /// Never stops the script.
+    fn none() -> Self {
+        Self {
+            cancel: None,
+            deadline: Deadline::new(None),
+        }
+    }
+
+    /// Termination token for the script to stop with, if it should.
+    fn check(&self) -> Option<&'static str> {
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|c| c.load(Ordering::Relaxed))
+        {
+            Some(CANCEL_TOKEN)
+        } else if self.deadline.has_passed() {
+            Some(limits::TIMEOUT_TOKEN)
+        } else {
+            None
+        }
+    }
+}
+
+/// Sleep for `ms` milliseconds, or none if negative, in short slices so that
+/// cancelling or the timeout stop the script with the same error as between
+/// operations.
+fn sleep_ms(interrupt: &Interrupt, ms: i64) -> Result<(), Box<EvalAltResult>> {
+    let mut left = Duration::from_millis(ms.max(0).unsigned_abs());
+    while !left.is_zero() {
+        if let Some(token) = interrupt.check() {
+            return Err(EvalAltResult::ErrorTerminated(token.into(), Position::NONE).into());
+        }
+        let slice = left.min(SLEEP_SLICE);
+        std::thread::sleep(slice);
+        left -= slice;
+    }
+    match interrupt.check() {
+        Some(token) => Err(EvalAltResult::ErrorTerminated(token.into(), Position::NONE).into()),
+        None => Ok(()),
+    }
+}
+
+fn rand_int(min: i64, max: i64) -> i64 {
+    rand::thread_rng().gen_range(min..=max)
+}
+
+/// Register the custom Rust types and helper functions available to scripts.
+///
+/// Functions whose capability is not in `granted` are replaced by stubs that
+/// fail with a "capability not granted" runtime error. Relative paths passed
+/// to `read_file` are resolved against the first of `base_paths` containing
+/// the file.
+pub fn register_host_api(engine: &mut Engine, granted: &[Capability], base_paths: &[PathBuf]) {
+    register_host_api_with(engine, granted, base_paths, Interrupt::none());
+}
+
+/// [`register_host_api`] with `sleep_ms` stopping early on `interrupt`.
+fn register_host_api_with(
+    engine: &mut Engine,
+    granted: &[Capability],
+    base_paths: &[PathBuf],
+    interrupt: Interrupt,
+) {
+    engine.build_type::<Point>();
+    FuncRegistration::new("Point")
+        .with_params_info(["x: i64", "y: i64", "Point"])
+        .with_comments(["/// Create a point from its `x` and `y` coordinates."])
+        .register_into_engine(engine, Point::new);
+    FuncRegistration::new("length")
+        .with_params_info(["point: &mut Point", "f64"])
+        .with_comments(["/// Distance of the point from the origin."])
+        .register_into_engine(engine, Point::length);
+    FuncRegistration::new("to_json")
+        .with_params_info(["value: Dynamic", "String"])
+        .with_comments(["/// Serialize a value to a JSON string."])
+        .register_into_engine(engine, to_json);
+    FuncRegistration::new("from_json")
+        .with_params_info(["json: &str", "Dynamic"])
+        .with_comments(["/// Parse a JSON string into a value, or `()` if it is not valid JSON."])
+        .register_into_engine(engine, from_json);
+    assert::register(engine);
+
+    let http_get_fn = FuncRegistration::new("http_get")
+        .with_params_info(["url: &str", "Dynamic"])
+        .with_comments([
+            "/// Fetch `url` and parse the response as JSON.",
+            "///",
+            "/// Requires the `net` capability.",
+        ]);
+    if granted.contains(&Capability::Net) {
+        http_get_fn.register_into_engine(engine, http_get);
+    } else {
+        http_get_fn.register_into_engine(
+            engine,
+            |_: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+                Err(capability::not_granted(Capability::Net, "http_get"))
+            },
+        );
+    }
+
+    let read_file_fn = FuncRegistration::new("read_file")
+        .with_params_info(["path: &str", "String"])
+        .with_comments([
+            "/// Read a text file relative to the example's directory.",
+            "///",
+            "/// Requires the `fs.read` capability.",
+        ]);
+    if granted.contains(&Capability::FsRead) {
+        let bases = base_paths.to_vec();
+        read_file_fn.register_into_engine(engine, move |path: &str| read_file(&bases, path));
+    } else {
+        read_file_fn.register_into_engine(
+            engine,
+            |_: &str| -> Result<String, Box<EvalAltResult>> {
+                Err(capability::not_granted(Capability::FsRead, "read_file"))
+            },
+        );
+    }
+
+    let sleep_ms_fn = FuncRegistration::new("sleep_ms")
+        .with_params_info(["ms: i64"])
+        .with_comments([
+            "/// Pause the script for `ms` milliseconds, or not at all if `ms` is negative.",
+            "///",
+            "/// Requires the `time` capability.",
+        ]);
+    if granted.contains(&Capability::Time) {
+        sleep_ms_fn.register_into_engine(engine, move |ms: i64| sleep_ms(&interrupt, ms));
+    } else {
+        sleep_ms_fn.register_into_engine(engine, |_: i64| -> Result<(), Box<EvalAltResult>> {
+            Err(capability::not_granted(Capability::Time, "sleep_ms"))
+        });
+    }
+
+    let rand_int_fn = FuncRegistration::new("rand_int")
+        .with_params_info(["min: i64", "max: i64", "i64"])
+        .with_comments([
+            "/// Random integer between `min` and `max`, inclusive.",
+            "///",
+            "/// Requires the `random` capability.",
+        ]);
+    if granted.contains(&Capability::Random) {
+        rand_int_fn.register_into_engine(engine, rand_int);
+    } else {
+        rand_int_fn.register_into_engine(
+            engine,
+            |_: i64, _: i64| -> Result<i64, Box<EvalAltResult>> {
+                Err(capability::not_granted(Capability::Random, "rand_int"))
+            },
+        );
+    }
+}
+
+/// Names of the functions registered by [`register_host_api`], sorted and
+/// without duplicates.
+pub fn host_function_names() -> Vec<String> {
+    let mut names: Vec<String> = HostEngine::builder()
+        .build()
+        .functions(None)
+        .into_iter()
+        .filter(|f| f.origin == FnOrigin::Host && !f.name.contains('$'))
+        .map(|f| f.name)
+        .collect();
+    names.dedup();
+    names
+}
+
+/// A Rhai [`Engine`] configured with the host API and output capture.
+pub struct HostEngine {
+    engine: Engine,
+    output: Arc<Mutex<String>>,
+}
+
+impl HostEngine {
+    /// Start configuring a new host engine.
+    pub fn builder() -> HostEngineBuilder {
+        HostEngineBuilder::default()
+    }
+
+    /// The underlying Rhai engine.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Describe every function available to scripts on this engine, including
+    /// those defined in `ast` if given.
+    pub fn functions(&self, ast: Option<&AST>) -> Vec<FnInfo> {
+        api::functions(&self.engine, ast)
+    }
+
+    /// Describe the custom types registered on this engine.
+    pub fn types(&self) -> Vec<TypeInfo> {
+        api::types(&self.engine)
+    }
+
+    /// Return a copy of everything printed so far via `print` or `debug`.
+    pub fn output(&self) -> String {
+        self.output.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Return everything printed so far and clear the captured output.
+    pub fn take_output(&self) -> String {
+        self.output
+            .lock()
+            .map(|mut s| std::mem::take(&mut *s))
+            .unwrap_or_default()
+    }
+}
+
+/// Builder for [`HostEngine`].
+#[derive(Default)]
+pub struct HostEngineBuilder {
+    module_base_paths: Vec<PathBuf>,
+    limits: Limits,
+    capabilities: Vec<Capability>,
+    cancel: Option<Arc<AtomicBool>>,
+    listener: Option<OutputListener>,
+    optimization_level: Option<OptimizationLevel>,
+    debugger: Option<DebuggerCallback>,
+}
+
+impl HostEngineBuilder {
+    /// Resolve `import` statements and `read_file` paths relative to this directory.
+    ///
+    /// When called more than once, the directories are searched in the order given.
+    pub fn module_base_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.module_base_paths.push(path.into());
+        self
+    }
+
+    /// Apply resource limits to scripts run on the engine.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Grant capabilities to scripts run on the engine. None are granted by default.
+    pub fn capabilities(mut self, capabilities: impl IntoIterator<Item = Capability>) -> Self {
+        self.capabilities.extend(capabilities);
+        self
+    }
+
+    /// Stop scripts as soon as `flag` becomes `true`.
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Stream each line of output to `listener` while the script runs.
+    ///
+    /// Debug lines carry the same `DEBUG: ` prefix as the captured output.
+    pub fn on_output(mut self, listener: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.listener = Some(Arc::new(listener));
+        self
+    }
+
+    /// Compile scripts with the given optimization level instead of Rhai's
+    /// default, [`OptimizationLevel::Simple`].
+    pub fn optimization_level(mut self, level: OptimizationLevel) -> Self {
+        self.optimization_level = Some(level);
+        self
+    }
+
+    /// Run scripts under Rhai's debugger, calling `callback` on each event.
+    ///
+    /// The callback is called on the thread running the script and may block
+    /// it to pause the script.
+    pub fn debugger(
+        mut self,
+        callback: impl Fn(
+            EvalContext,
+            DebuggerEvent,
+            ASTNode,
+            Option<&str>,
+            Position,
+        ) -> Result<DebuggerCommand, Box<EvalAltResult>>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.debugger = Some(Box::new(callback));
+        self
+    }
+
+    /// Create the engine, registering the host API and output capture.
+    pub fn build(self) -> HostEngine {
+        let output = Arc::new(Mutex::new(String::new()));
+        let mut engine = Engine::new();
+
+        if self.module_base_paths.len() > 1 {
+            let mut resolvers = ModuleResolversCollection::new();
+            for base in &self.module_base_paths {
+                resolvers.push(FileModuleResolver::new_with_path(base));
+            }
+            engine.set_module_resolver(resolvers);
+        } else {
+            let mut resolver = FileModuleResolver::new();
+            if let Some(base) = self.module_base_paths.first() {
+                resolver.set_base_path(base);
+            }
+            engine.set_module_resolver(resolver);
+        }
+
+        // Capture calls to `print` into our output buffer and forward them
+        // to the listener, if any.
+        let out = output.clone();
+        let listener = self.listener.clone();
+        engine.on_print(move |s| {
+            if let Ok(mut buf) = out.lock() {
+                buf.push_str(s);
+                buf.push('\n');
+            }
+            if let Some(listener) = &listener {
+                listener(s);
+            }
+        });
+
+        // Capture debug output as well.
+        let out_dbg = output.clone();
+        let listener = self.listener;
+        engine.on_debug(move |s, _, _| {
+            let line = format!("DEBUG: {s}");
+            if let Ok(mut buf) = out_dbg.lock() {
+                buf.push_str(&line);
+                buf.push('\n');
+            }
+            if let Some(listener) = &listener {
+                listener(&line);
+            }
+        });
+
+        let interrupt = Interrupt {
+            cancel: self.cancel,
+            deadline: Deadline::new(self.limits.timeout()),
+        };
+        register_host_api_with(
+            &mut engine,
+            &self.capabilities,
+            &self.module_base_paths,
+            interrupt.clone(),
+        );
+        self.limits.apply(&mut engine);
+        if let Some(level) = self.optimization_level {
+            engine.set_optimization_level(level);
+        }
+        if let Some(callback) = self.debugger {
+            // The debugger interface is only flagged as volatile.
+            #[allow(deprecated)]
+            engine.register_debugger(|_, debugger| debugger, callback);
+        }
+
+        // Check for cancellation and the wall-clock timeout between operations.
+        // Every `eval` or `call_fn` counts operations from 1, so the timeout
+        // starts with the first operation of each and compiling does not count.
+        if interrupt.deadline.is_enabled() || interrupt.cancel.is_some() {
+            engine.on_progress(move |operations| {
+                if operations == 1 {
+                    interrupt.deadline.start();
+                }
+                interrupt.check().map(Into::into)
+            });
+        }
+
+        HostEngine { engine, output }
+    }
+}
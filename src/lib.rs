@@ -1 +1,5 @@
+#![allow(non_snake_case)]
+
 pub mod examples;
+pub mod host;
+pub mod workspace;
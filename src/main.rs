@@ -1,13 +1,40 @@
 //! Entry point for the Rhai Learning application.
+//!
+//! Without arguments the `egui` interface is started; otherwise the
+//! arguments select a headless command, see [`cli::Command`].
 
-mod examples;
+use Rhai_Learning::examples;
+use Rhai_Learning::workspace::Workspace;
+use std::process::ExitCode;
+
+mod cli;
 mod ui;
 
-fn main() -> eframe::Result<()> {
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let root = match cli::take_root(&mut args) {
+        Ok(root) => root,
+        Err(msg) => return cli::usage_error(&msg),
+    };
+    let workspace = Workspace::detect(root);
+    match cli::Command::parse(&args) {
+        Ok(Some(command)) => command.execute(&workspace),
+        Ok(None) => match run_gui(workspace) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e}");
+                ExitCode::FAILURE
+            }
+        },
+        Err(msg) => cli::usage_error(&msg),
+    }
+}
+
+fn run_gui(workspace: Workspace) -> eframe::Result<()> {
     let options = eframe::NativeOptions::default();
     eframe::run_native(
         "Rhai Learning",
         options,
-        Box::new(|_cc| Ok(Box::new(ui::app::App::default()))),
+        Box::new(|_cc| Ok(Box::new(ui::app::App::new(workspace)))),
     )
 }
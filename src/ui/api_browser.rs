@@ -0,0 +1,328 @@
+//! Searchable catalogue of everything the selected example's script can call.
+
+use crate::examples::{Example, Import};
+use Rhai_Learning::host::{FnInfo, FnOrigin, TypeInfo};
+use eframe::egui;
+use std::sync::mpsc::{Receiver, channel};
+use std::time::Duration;
+
+/// How often to look for the catalogue being built.
+const POLL: Duration = Duration::from_millis(50);
+
+/// Group an [`ApiEntry`] is listed under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    /// Function defined in the edited script.
+    Script,
+    /// Public function of a module the script imports.
+    Module,
+    /// Function registered by the host.
+    Host,
+    /// Custom type registered by the host, or one of its methods.
+    Type,
+    /// Function from Rhai's standard packages.
+    Package,
+    /// Operator such as `+` or `==`.
+    Operator,
+}
+
+impl Category {
+    /// Every category, in the order they are listed.
+    pub const ALL: [Category; 6] = [
+        Category::Script,
+        Category::Module,
+        Category::Host,
+        Category::Type,
+        Category::Package,
+        Category::Operator,
+    ];
+
+    /// Name shown in the category filter.
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Script => "Script",
+            Category::Module => "Modules",
+            Category::Host => "Host",
+            Category::Type => "Types",
+            Category::Package => "Packages",
+            Category::Operator => "Operators",
+        }
+    }
+}
+
+/// One function, type or operator in the catalogue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiEntry {
+    /// Group the entry is listed under.
+    pub category: Category,
+    /// Signature shown in the list.
+    pub signature: String,
+    /// Documentation, empty if there is none.
+    pub doc: String,
+    /// Text inserted into the editor when the entry is selected.
+    pub template: String,
+}
+
+impl ApiEntry {
+    fn matches(&self, filter: &str) -> bool {
+        filter.is_empty()
+            || self.signature.to_lowercase().contains(filter)
+            || self.doc.to_lowercase().contains(filter)
+    }
+}
+
+/// Name of each parameter of `f`, for use in a call template.
+fn arg_names(f: &FnInfo) -> Vec<String> {
+    f.params
+        .iter()
+        .enumerate()
+        .map(|(i, p)| p.name.clone().unwrap_or_else(|| format!("arg{}", i + 1)))
+        .collect()
+}
+
+fn call_template(name: &str, f: &FnInfo) -> String {
+    format!("{name}({})", arg_names(f).join(", "))
+}
+
+/// Template for an operator: `!x`, `-x` or `x + y`.
+fn operator_template(f: &FnInfo) -> String {
+    match arg_names(f).as_slice() {
+        [operand] => format!("{}{operand}", f.name),
+        [lhs, rhs, ..] => format!("{lhs} {} {rhs}", f.name),
+        [] => f.name.clone(),
+    }
+}
+
+/// The custom type taken as first parameter by `f`, if any.
+fn receiver<'a>(f: &FnInfo, types: &'a [TypeInfo]) -> Option<&'a TypeInfo> {
+    let first = f.params.first()?.typ.as_deref()?;
+    let first = first.trim_start_matches("&mut ").trim();
+    types.iter().find(|t| t.name == first)
+}
+
+/// Build the catalogue from the engine's functions and custom types and the
+/// public functions of each imported module.
+///
+/// Property accessors, indexers and modules imported without an alias are
+/// left out since scripts cannot call them by name.
+pub fn catalogue(
+    functions: &[FnInfo],
+    types: &[TypeInfo],
+    modules: &[(Import, Vec<FnInfo>)],
+) -> Vec<ApiEntry> {
+    let mut entries = Vec::new();
+    for t in types {
+        let constructor = functions
+            .iter()
+            .find(|f| f.origin == FnOrigin::Host && f.name == t.name);
+        entries.push(ApiEntry {
+            category: Category::Type,
+            signature: match constructor {
+                Some(f) => format!("type {} = {}", t.name, f.signature),
+                None => format!("type {}", t.name),
+            },
+            doc: t.doc(),
+            template: constructor.map_or_else(|| t.name.clone(), |f| call_template(&t.name, f)),
+        });
+    }
+    for f in functions {
+        if f.name.contains('$') {
+            continue;
+        }
+        let (category, template) = match f.origin {
+            FnOrigin::Script => (Category::Script, call_template(&f.name, f)),
+            FnOrigin::Host => match receiver(f, types) {
+                Some(t) if t.name != f.name => {
+                    let args = arg_names(f);
+                    let object = t.name.to_lowercase();
+                    let call = format!("{object}.{}({})", f.name, args[1..].join(", "));
+                    (Category::Type, call)
+                }
+                Some(_) => continue,
+                None => (Category::Host, call_template(&f.name, f)),
+            },
+            FnOrigin::Builtin if f.is_operator() => (Category::Operator, operator_template(f)),
+            FnOrigin::Builtin => (Category::Package, call_template(&f.name, f)),
+        };
+        entries.push(ApiEntry {
+            category,
+            signature: f.signature.clone(),
+            doc: f.doc(),
+            template,
+        });
+    }
+    for (import, functions) in modules {
+        let Some(alias) = &import.alias else {
+            continue;
+        };
+        for f in functions {
+            let name = format!("{alias}::{}", f.name);
+            entries.push(ApiEntry {
+                category: Category::Module,
+                signature: format!("{alias}::{}({})", f.name, f.param_list()),
+                doc: f.doc(),
+                template: call_template(&name, f),
+            });
+        }
+    }
+    entries.sort_by(|a, b| (a.category, &a.signature).cmp(&(b.category, &b.signature)));
+    entries.dedup();
+    entries
+}
+
+/// Window listing the [`catalogue`] of the selected example, filtered by a
+/// search string and category.
+#[derive(Default)]
+pub struct ApiBrowser {
+    /// Example id and script text the entries were built from.
+    source: Option<(String, String)>,
+    entries: Vec<ApiEntry>,
+    /// Receives the catalogue being built for `source`.
+    rx: Option<Receiver<Vec<ApiEntry>>>,
+    filter: String,
+    category: Option<Category>,
+}
+
+impl ApiBrowser {
+    /// Rebuild the catalogue on a background thread if the example or its
+    /// script changed since the last call, keeping the previous entries until
+    /// it is done.
+    ///
+    /// Returns when to call again, if the catalogue is being built.
+    pub fn update(&mut self, example: &Example, script: &str) -> Option<Duration> {
+        if let Some(rx) = &self.rx
+            && let Ok(entries) = rx.try_recv()
+        {
+            self.entries = entries;
+            self.rx = None;
+        }
+        if self
+            .source
+            .as_ref()
+            .is_some_and(|(id, text)| *id == example.id && text == script)
+        {
+            return self.rx.as_ref().map(|_| POLL);
+        }
+        self.source = Some((example.id.clone(), script.to_string()));
+        // Compiling the script and reading its modules can take a while, so it
+        // is kept off the UI thread. A build for an older script is dropped.
+        let (tx, rx) = channel();
+        let example = example.clone();
+        let script = script.to_string();
+        std::thread::spawn(move || {
+            let host = example.host_engine();
+            let ast = example.check_script(&script).ok();
+            let entries = catalogue(
+                &host.functions(ast.as_ref()),
+                &host.types(),
+                &example.module_functions(&script),
+            );
+            let _ = tx.send(entries);
+        });
+        self.rx = Some(rx);
+        Some(POLL)
+    }
+
+    /// Show the search field and the matching entries.
+    ///
+    /// Returns the template of the entry the user clicked, if any.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<String> {
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+        ui.horizontal_wrapped(|ui| {
+            ui.selectable_value(&mut self.category, None, "All");
+            for category in Category::ALL {
+                ui.selectable_value(&mut self.category, Some(category), category.label());
+            }
+        });
+        ui.separator();
+
+        let filter = self.filter.to_lowercase();
+        let shown: Vec<&ApiEntry> = self
+            .entries
+            .iter()
+            .filter(|e| self.category.is_none_or(|c| c == e.category))
+            .filter(|e| e.matches(&filter))
+            .collect();
+        if shown.is_empty() {
+            ui.weak(if self.rx.is_some() {
+                "Loading…"
+            } else {
+                "Nothing matches"
+            });
+            return None;
+        }
+        let mut chosen = None;
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                for entry in &shown[rows] {
+                    ui.horizontal(|ui| {
+                        let label = egui::RichText::new(&entry.signature).monospace();
+                        let mut item = ui.selectable_label(false, label);
+                        let summary = entry.doc.lines().next().unwrap_or_default();
+                        if !entry.doc.is_empty() {
+                            item = item.on_hover_text(&entry.doc);
+                        }
+                        if item.clicked() {
+                            chosen = Some(entry.template.clone());
+                        }
+                        ui.weak(summary);
+                    });
+                }
+            });
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Rhai_Learning::host::HostEngine;
+
+    #[test]
+    fn catalogue_offers_call_templates_for_each_category() {
+        let host = HostEngine::builder().build();
+        let ast = host.engine().compile("fn twice(x) { x * 2 }").unwrap();
+        let import = Import {
+            path: "math_utils".to_string(),
+            alias: Some("math".to_string()),
+            line: 1,
+        };
+        let module = host.engine().compile("fn square(x) { x * x }").unwrap();
+        let module_fns = host
+            .functions(Some(&module))
+            .into_iter()
+            .filter(|f| f.origin == FnOrigin::Script)
+            .collect();
+        let entries = catalogue(
+            &host.functions(Some(&ast)),
+            &host.types(),
+            &[(import, module_fns)],
+        );
+        let template = |category: Category, signature: &str| {
+            entries
+                .iter()
+                .find(|e| e.category == category && e.signature.starts_with(signature))
+                .map(|e| e.template.as_str())
+        };
+
+        assert_eq!(template(Category::Script, "twice("), Some("twice(x)"));
+        assert_eq!(
+            template(Category::Module, "math::square("),
+            Some("math::square(x)")
+        );
+        assert_eq!(
+            template(Category::Host, "rand_int("),
+            Some("rand_int(min, max)")
+        );
+        assert_eq!(template(Category::Type, "type Point"), Some("Point(x, y)"));
+        assert_eq!(template(Category::Type, "length("), Some("point.length()"));
+        assert_eq!(template(Category::Operator, "+(x: i64, y: "), Some("x + y"));
+        assert!(template(Category::Package, "len(").is_some());
+        assert!(entries.iter().all(|e| !e.signature.contains('$')));
+    }
+}
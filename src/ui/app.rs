@@ -1,12 +1,59 @@
 //! eframe/`egui` application displaying and executing Rhai examples.
 
-use crate::examples::{Example, ExampleRegistry};
+use super::api_browser::ApiBrowser;
+use super::ast_view::AstView;
+use super::complete::{Completion, function_completions};
+use super::document::{DiffLine, DiskChange, Document, line_diff};
+use super::editor::{self, LineMarks};
+use super::live_check::LiveCheck;
+use super::optimize_view;
+use super::scope_view;
+use super::tabs::Tabs;
+use crate::examples::{
+    Breakpoint, DebugCommand, DebugSession, Example, ExampleRegistry, LevelRun, Pause, RunHandle,
+    RunResult, ScriptError, TestOutcome, TestReport, Variable, script_imports, script_read_files,
+};
+use Rhai_Learning::host::HostEngine;
+use Rhai_Learning::workspace::Workspace;
 use eframe::egui;
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{Receiver, channel};
+use std::time::Duration;
+
+/// An action that would discard unsaved changes, waiting for the user to
+/// save or discard them first.
+enum Pending {
+    /// Select the example at this index.
+    Select(usize),
+    /// Close the tab at this index.
+    CloseTab(usize),
+    /// Delete the user copy of the selected bundled example.
+    ResetToOriginal,
+    /// Close the application.
+    Quit,
+}
+
+/// A location in the main script (`path` is `None`) or in a module file.
+#[derive(Clone, PartialEq)]
+struct Location {
+    path: Option<PathBuf>,
+    line: usize,
+    column: usize,
+}
+
+/// Appended to the names of bundled examples that have a modified user copy.
+const MODIFIED_MARKER: &str = " (modified)";
+
+/// Appended to the names of documents with unsaved changes.
+const DIRTY_MARKER: &str = " *";
 
 /// Top-level application state for the Rhai learning UI.
 pub struct App {
+    /// Workspace the examples and logs are loaded from.
+    workspace: Workspace,
     examples: Vec<Example>,
     selected: Option<usize>,
     console: String,
@@ -15,21 +62,78 @@ pub struct App {
     watcher: RecommendedWatcher,
     watch_rx: Receiver<Event>,
     reload_notice: Option<String>,
+    /// Problems found while loading the manifest, shown in a banner.
+    manifest_problems: Vec<String>,
     /// Current filter for example names.
     filter: String,
-    /// Editable script text of the currently selected example.
-    script: String,
-    /// ID of the example whose script is loaded in `script`.
+    /// Files open in the editor, starting with the selected example's script.
+    tabs: Tabs,
+    /// ID of the example whose script is loaded in the script tab.
     loaded_script: Option<String>,
-    /// Debug-format of the last compiled AST.
-    ast_text: String,
+    /// Files under the example directories, listed by the Open file menu.
+    files: Vec<(String, PathBuf)>,
+    /// Files the active tab links to, listed by the Follow links.
+    links: Vec<(String, PathBuf)>,
+    /// Path and text of the tab `links` were found in.
+    links_source: Option<(PathBuf, String)>,
+    /// Outcome of the last export of the AST shown in the AST window.
+    ast_status: String,
     /// Whether the AST window is open.
     show_ast: bool,
+    /// Selection in the syntax tree shown in the AST window.
+    ast_view: AstView,
+    /// Script currently running on a background thread.
+    running: Option<RunHandle>,
+    /// Script currently running under the debugger.
+    debugging: Option<DebugSession>,
+    /// Where the debugged script is paused.
+    pause: Option<Pause>,
+    /// Location of the paused line marked in the editor.
+    pause_location: Option<Location>,
+    /// Breakpoint lines by file, `None` for the main script.
+    breakpoints: BTreeMap<Option<PathBuf>, BTreeSet<usize>>,
+    /// Whether the Debugger window is open.
+    show_debugger: bool,
+    /// Variables left in scope by the last run.
+    variables: Vec<Variable>,
+    /// Whether the Variables window is open.
+    show_variables: bool,
+    /// Wall-clock duration of the last finished run.
+    last_elapsed: Option<Duration>,
+    /// Error raised by the last run, rendered as clickable locations.
+    last_error: Option<ScriptError>,
+    /// Action waiting for unsaved changes to be saved or discarded.
+    pending: Option<Pending>,
+    /// Location of the error line highlighted in the editor.
+    error_location: Option<Location>,
+    /// Characters of the active tab to select and scroll to on the next frame.
+    jump_to: Option<Range<usize>>,
+    /// Receives the report of tests running on a background thread.
+    tests_rx: Option<Receiver<TestReport>>,
+    /// Report of the last finished test run.
+    test_report: Option<TestReport>,
+    /// Whether the Tests window is open.
+    show_tests: bool,
+    /// Receives the runs of the script under each optimization level.
+    optimize_rx: Option<Receiver<Vec<LevelRun>>>,
+    /// Runs of the last optimization comparison.
+    level_runs: Vec<LevelRun>,
+    /// Whether the Optimization window is open.
+    show_optimize: bool,
+    /// Compiles the edited text in the background to report syntax errors.
+    live_check: LiveCheck,
+    /// Completions for the functions registered on the example engine.
+    completions: Vec<Completion>,
+    /// Catalogue of the functions and types available to the selected example.
+    api_browser: ApiBrowser,
+    /// Whether the API window is open.
+    show_api: bool,
 }
 
-impl Default for App {
-    fn default() -> Self {
-        let examples = ExampleRegistry::all();
+impl App {
+    /// Create the application showing the examples of `workspace`.
+    pub fn new(workspace: Workspace) -> Self {
+        let (examples, mut manifest_problems) = load_examples(&workspace);
         let (tx, rx) = channel();
         let mut watcher = notify::recommended_watcher(move |res| {
             if let Ok(event) = res {
@@ -37,10 +141,17 @@ impl Default for App {
             }
         })
         .expect("failed to create watcher");
-        watcher
-            .watch(std::path::Path::new("examples"), RecursiveMode::Recursive)
-            .expect("failed to watch examples");
-        Self {
+        let user_dir = workspace.user_examples_dir();
+        if let Some(dir) = &user_dir {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        for dir in std::iter::once(workspace.examples_dir()).chain(user_dir) {
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::Recursive) {
+                manifest_problems.push(format!("cannot watch {}: {e}", dir.display()));
+            }
+        }
+        let mut app = Self {
+            workspace,
             examples,
             selected: None,
             console: String::new(),
@@ -48,85 +159,907 @@ impl Default for App {
             watcher,
             watch_rx: rx,
             reload_notice: None,
+            manifest_problems,
             filter: String::new(),
-            script: String::new(),
+            tabs: Tabs::default(),
             loaded_script: None,
-            ast_text: String::new(),
+            files: Vec::new(),
+            links: Vec::new(),
+            links_source: None,
+            ast_status: String::new(),
             show_ast: false,
+            ast_view: AstView::default(),
+            running: None,
+            debugging: None,
+            pause: None,
+            pause_location: None,
+            breakpoints: BTreeMap::new(),
+            show_debugger: false,
+            variables: Vec::new(),
+            show_variables: false,
+            last_elapsed: None,
+            last_error: None,
+            pending: None,
+            error_location: None,
+            jump_to: None,
+            tests_rx: None,
+            test_report: None,
+            show_tests: false,
+            optimize_rx: None,
+            level_runs: Vec::new(),
+            show_optimize: false,
+            live_check: LiveCheck::default(),
+            completions: function_completions(&HostEngine::builder().build().functions(None)),
+            api_browser: ApiBrowser::default(),
+            show_api: false,
+        };
+        app.refresh_files();
+        app
+    }
+}
+
+/// Load the examples of `workspace`, describing any manifest problems for the banner.
+fn load_examples(workspace: &Workspace) -> (Vec<Example>, Vec<String>) {
+    match ExampleRegistry::load(workspace) {
+        Ok(registry) => {
+            let problems = registry.problems().iter().map(|p| p.to_string()).collect();
+            (registry.into_examples(), problems)
         }
+        Err(err) => (Vec::new(), vec![err.to_string()]),
     }
 }
 
 impl App {
+    /// Reload the manifest, keeping the selected example if it still exists.
+    fn reload_examples(&mut self) {
+        let selected_id = self
+            .selected
+            .and_then(|i| self.examples.get(i).map(|e| e.id.clone()));
+        (self.examples, self.manifest_problems) = load_examples(&self.workspace);
+        self.refresh_files();
+        self.selected = selected_id.and_then(|id| self.examples.iter().position(|e| e.id == id));
+        if self.selected.is_some() {
+            self.sync_with_disk();
+            if self
+                .tabs
+                .get(Tabs::SCRIPT)
+                .is_some_and(|t| t.conflict.is_none())
+            {
+                self.run_selected();
+            }
+        }
+    }
+
+    /// Pick up changes made to the open files outside the editor.
+    ///
+    /// Tabs without unsaved changes are reloaded; the others get a conflict
+    /// for the user to resolve.
+    fn sync_with_disk(&mut self) {
+        let script_path = self
+            .selected
+            .and_then(|i| self.examples.get(i))
+            .filter(|e| self.loaded_script.as_ref() == Some(&e.id))
+            .map(|e| e.script_path.clone());
+        for (i, tab) in self.tabs.iter_mut().enumerate() {
+            let path = match &script_path {
+                Some(path) if i == Tabs::SCRIPT => path.clone(),
+                _ if i == Tabs::SCRIPT => continue,
+                _ => tab.doc.path.clone(),
+            };
+            tab.sync(path);
+        }
+    }
+
+    fn is_dirty(&self, tab: usize) -> bool {
+        self.tabs.get(tab).is_some_and(|t| t.doc.is_dirty())
+    }
+
+    /// Tabs with unsaved changes that `action` would discard.
+    fn discarded_by(&self, action: &Pending) -> Vec<usize> {
+        let tabs: Vec<usize> = match action {
+            Pending::Select(_) | Pending::Quit => (0..self.tabs.iter().count()).collect(),
+            Pending::ResetToOriginal => vec![Tabs::SCRIPT],
+            Pending::CloseTab(i) => vec![*i],
+        };
+        tabs.into_iter().filter(|t| self.is_dirty(*t)).collect()
+    }
+
+    /// Perform `action` now, or ask first if it would discard unsaved changes.
+    fn request(&mut self, ctx: &egui::Context, action: Pending) {
+        if !self.discarded_by(&action).is_empty() {
+            self.pending = Some(action);
+        } else {
+            self.perform(ctx, action);
+        }
+    }
+
+    fn perform(&mut self, ctx: &egui::Context, action: Pending) {
+        match action {
+            Pending::Select(i) => {
+                self.stop_debugging();
+                self.selected = Some(i);
+                self.loaded_script = None;
+                self.error_location = None;
+                self.breakpoints.remove(&None);
+            }
+            Pending::CloseTab(i) => self.tabs.close(i),
+            Pending::ResetToOriginal => self.reset_selected(),
+            Pending::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+        }
+    }
+
+    /// Save the tab at `index`, returning whether it was written.
+    fn save(&mut self, index: usize) -> bool {
+        let result = if index == Tabs::SCRIPT {
+            self.save_selected()
+        } else {
+            self.save_file(index)
+        };
+        if let Err(e) = &result {
+            self.console = format!("Save failed: {e}");
+        }
+        result.is_ok()
+    }
+
+    /// Save the script, creating a user copy for bundled examples.
+    fn save_selected(&mut self) -> std::io::Result<()> {
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get_mut(idx)
+        {
+            let script = self.tabs.script_mut();
+            example.save_script(&script.text)?;
+            script.mark_saved(example.script_path.clone());
+        }
+        Ok(())
+    }
+
+    /// Save a module or data file. Bundled files are saved to a user copy.
+    fn save_file(&mut self, index: usize) -> std::io::Result<()> {
+        let Some(tab) = self.tabs.get_mut(index) else {
+            return Ok(());
+        };
+        match self.workspace.user_copy_path(&tab.doc.path) {
+            Some(copy) => {
+                if let Some(dir) = copy.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::write(&copy, &tab.doc.text)?;
+                tab.doc.mark_saved(copy);
+                Ok(())
+            }
+            None => tab.doc.save(),
+        }
+    }
+
+    /// Drop the user copy of the selected bundled example and show the original.
+    fn reset_selected(&mut self) {
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get_mut(idx)
+        {
+            match example.reset_to_original() {
+                Ok(()) => {
+                    let path = example.script_path.clone();
+                    *self.tabs.script_mut() = Document::open(path);
+                }
+                Err(e) => self.console = format!("Reset failed: {e}"),
+            }
+        }
+    }
+
     fn load_selected_script(&mut self) {
-        if let Some(idx) = self.selected {
-            if let Some(example) = self.examples.get(idx) {
-                self.script = std::fs::read_to_string(&example.script_path).unwrap_or_default();
-                self.loaded_script = Some(example.id.clone());
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get(idx)
+        {
+            self.tabs.reset(Document::open(example.script_path.clone()));
+            self.loaded_script = Some(example.id.clone());
+        }
+    }
+
+    /// Open `path` in a tab, preferring the user copy of a bundled file.
+    fn open_file(&mut self, path: PathBuf) -> usize {
+        let path = self
+            .workspace
+            .user_copy_path(&path)
+            .filter(|copy| copy.is_file())
+            .unwrap_or(path);
+        self.tabs.open(path)
+    }
+
+    /// List the example files again and find the links of the active tab
+    /// again, after files were added, removed or shadowed by user copies.
+    fn refresh_files(&mut self) {
+        self.files = self.example_files();
+        self.links_source = None;
+        self.refresh_links();
+    }
+
+    /// Find the links of the active tab if it or its text changed since they
+    /// were last found.
+    fn refresh_links(&mut self) {
+        let Some(tab) = self.tabs.get(self.tabs.active()) else {
+            self.links.clear();
+            self.links_source = None;
+            return;
+        };
+        if self
+            .links_source
+            .as_ref()
+            .is_some_and(|(path, text)| *path == tab.doc.path && *text == tab.doc.text)
+        {
+            return;
+        }
+        self.links_source = Some((tab.doc.path.clone(), tab.doc.text.clone()));
+        self.links = self.linked_files();
+    }
+
+    /// Files the active tab refers to through `import` and `read_file`.
+    fn linked_files(&self) -> Vec<(String, PathBuf)> {
+        let Some(example) = self.selected.and_then(|i| self.examples.get(i)) else {
+            return Vec::new();
+        };
+        let Some(tab) = self.tabs.get(self.tabs.active()) else {
+            return Vec::new();
+        };
+        let imports = script_imports(&tab.doc.text).into_iter().map(|import| {
+            let mut file = import.path;
+            if Path::new(&file).extension().is_none() {
+                file.push_str(".rhai");
+            }
+            file
+        });
+        imports
+            .chain(script_read_files(&tab.doc.text))
+            .filter_map(|file| Some((file.clone(), example.resolve_file(&file)?)))
+            .collect()
+    }
+
+    /// Files under the bundled and user example directories, by relative name.
+    fn example_files(&self) -> Vec<(String, PathBuf)> {
+        let mut files = Vec::new();
+        let dirs = std::iter::once(self.workspace.examples_dir())
+            .chain(self.workspace.user_examples_dir());
+        for dir in dirs {
+            for path in list_files(&dir) {
+                let name = path
+                    .strip_prefix(&dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .into_owned();
+                if !files.iter().any(|(n, _)| n == &name) {
+                    files.push((name, path));
+                }
             }
         }
+        files.sort();
+        files
     }
 
     fn run_selected(&mut self) {
-        if let Some(idx) = self.selected {
-            if let Some(example) = self.examples.get(idx) {
-                let result = example.run_script(&self.script);
-                self.console.clear();
-                if !result.stdout.is_empty() {
-                    self.console.push_str(&result.stdout);
+        self.stop_debugging();
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get(idx)
+        {
+            if let Some(handle) = self.running.take() {
+                handle.cancel();
+            }
+            self.console.clear();
+            self.last_error = None;
+            self.error_location = None;
+            self.running = Some(example.spawn_script(self.tabs.script().text.clone()));
+        }
+    }
+
+    /// Run the script under the debugger, pausing at the first statement
+    /// with `stop_on_entry` and otherwise at the first breakpoint.
+    fn debug_selected(&mut self, stop_on_entry: bool) {
+        self.stop_debugging();
+        let breakpoints = self.breakpoint_list();
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get(idx)
+        {
+            if let Some(handle) = self.running.take() {
+                handle.cancel();
+            }
+            self.console.clear();
+            self.last_error = None;
+            self.error_location = None;
+            let script = self.tabs.script().text.clone();
+            self.debugging = Some(example.debug_script(script, breakpoints, stop_on_entry));
+            self.show_debugger = true;
+        }
+    }
+
+    fn stop_debugging(&mut self) {
+        if let Some(mut session) = self.debugging.take() {
+            session.stop();
+        }
+        self.pause = None;
+        self.pause_location = None;
+    }
+
+    /// Resume the paused script with `command`.
+    fn resume(&mut self, command: DebugCommand) {
+        if let Some(session) = &mut self.debugging {
+            session.resume(command);
+        }
+        self.pause = None;
+        self.pause_location = None;
+    }
+
+    /// Every breakpoint set in the editor.
+    fn breakpoint_list(&self) -> Vec<Breakpoint> {
+        self.breakpoints
+            .iter()
+            .flat_map(|(path, lines)| {
+                lines.iter().map(|&line| Breakpoint {
+                    path: path.clone(),
+                    line,
+                })
+            })
+            .collect()
+    }
+
+    /// Stream output of the debugged script into the console, show where it
+    /// pauses and collect its result once it has finished.
+    fn poll_debug(&mut self) {
+        let Some(session) = &mut self.debugging else {
+            return;
+        };
+        for line in session.run().new_output() {
+            self.console.push_str(&line);
+            self.console.push('\n');
+        }
+        let pause = session.paused().cloned();
+        if pause != self.pause {
+            self.pause_location = pause.as_ref().and_then(|p| {
+                let path = self.reveal(p.source.as_deref(), p.line, p.column)?;
+                Some(Location {
+                    path,
+                    line: p.line,
+                    column: p.column,
+                })
+            });
+            self.pause = pause;
+        }
+        let Some(session) = &self.debugging else {
+            return;
+        };
+        let Some(result) = session.run().try_result() else {
+            return;
+        };
+        self.last_elapsed = Some(session.run().elapsed());
+        self.debugging = None;
+        self.pause = None;
+        self.pause_location = None;
+        self.show_result(&result);
+    }
+
+    /// Run the `test_*` functions of the selected example on a background thread.
+    fn run_selected_tests(&mut self) {
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get(idx)
+        {
+            let (tx, rx) = channel();
+            let example = example.clone();
+            let script = self.tabs.script().text.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(example.run_tests(&script));
+            });
+            self.tests_rx = Some(rx);
+            self.show_tests = true;
+        }
+    }
+
+    fn poll_tests(&mut self) {
+        if let Some(rx) = &self.tests_rx
+            && let Ok(report) = rx.try_recv()
+        {
+            self.test_report = Some(report);
+            self.tests_rx = None;
+        }
+    }
+
+    /// Compile and run the script under each optimization level on a
+    /// background thread.
+    fn compare_optimizations(&mut self) {
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get(idx)
+        {
+            let (tx, rx) = channel();
+            let example = example.clone();
+            let script = self.tabs.script().text.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(example.compare_optimizations(&script));
+            });
+            self.optimize_rx = Some(rx);
+            self.show_optimize = true;
+        }
+    }
+
+    fn poll_optimizations(&mut self) {
+        if let Some(rx) = &self.optimize_rx
+            && let Ok(runs) = rx.try_recv()
+        {
+            self.level_runs = runs;
+            self.optimize_rx = None;
+        }
+    }
+
+    /// Stream output from a background run into the console and collect its
+    /// result once it has finished.
+    fn poll_run(&mut self) {
+        let Some(handle) = &self.running else {
+            return;
+        };
+        for line in handle.new_output() {
+            self.console.push_str(&line);
+            self.console.push('\n');
+        }
+        let Some(result) = handle.try_result() else {
+            return;
+        };
+        self.last_elapsed = Some(handle.elapsed());
+        self.running = None;
+        self.show_result(&result);
+    }
+
+    fn show_result(&mut self, result: &RunResult) {
+        self.console.clear();
+        if !result.stdout.is_empty() {
+            self.console.push_str(&result.stdout);
+        }
+        if result.error.is_none() {
+            self.console.push_str(&format!("=> {}", result.value));
+        }
+        self.last_error = result.error.clone();
+        if let Some(err) = &self.last_error
+            && let Some(line) = err.line
+        {
+            let source = err.source.clone();
+            self.goto_location(source.as_deref(), line, err.column.unwrap_or(1));
+        }
+
+        self.variables = result.variables();
+
+        if let Some(idx) = self.selected
+            && let Some(example) = self.examples.get(idx)
+        {
+            self.logs = std::fs::read_to_string(&example.log_path).unwrap_or_default();
+        }
+    }
+}
+
+impl App {
+    /// Resolve a module source reported by Rhai against the example's directories.
+    fn module_path(&self, source: &str) -> Option<PathBuf> {
+        self.examples.get(self.selected?)?.source_path(source)
+    }
+
+    /// Show `line`/`column` of the main script or of module `source` in the
+    /// editor, returning the path of its tab as used by [`Location`].
+    fn reveal(
+        &mut self,
+        source: Option<&str>,
+        line: usize,
+        column: usize,
+    ) -> Option<Option<PathBuf>> {
+        let path = match source {
+            Some(source) => {
+                let path = self.module_path(source)?;
+                let index = self.open_file(path);
+                self.tabs.get(index).map(|t| t.doc.path.clone())
+            }
+            None => {
+                self.tabs.set_active(Tabs::SCRIPT);
+                None
+            }
+        };
+        self.jump_to = self.tabs.get(self.tabs.active()).map(|tab| {
+            let at = editor::char_index(&tab.doc.text, line, column);
+            at..at
+        });
+        Some(path)
+    }
+
+    /// Show `line`/`column` of the main script or of module `source` in the
+    /// editor and highlight it as the error line.
+    fn goto_location(&mut self, source: Option<&str>, line: usize, column: usize) {
+        if let Some(path) = self.reveal(source, line, column) {
+            self.error_location = Some(Location { path, line, column });
+        }
+    }
+}
+
+impl App {
+    /// Ask whether to save or discard changes before the pending action.
+    fn show_pending_prompt(&mut self, ctx: &egui::Context) {
+        let Some(action) = &self.pending else {
+            return;
+        };
+        let tabs = self.discarded_by(action);
+        if tabs.is_empty() {
+            // Changes were saved or reverted in the meantime.
+            if let Some(action) = self.pending.take() {
+                self.perform(ctx, action);
+            }
+            return;
+        }
+
+        let mut choice = None;
+        egui::Window::new("Unsaved changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                for tab in tabs.iter().filter_map(|i| self.tabs.get(*i)) {
+                    ui.label(format!("{} has unsaved changes.", tab.doc.path.display()));
                 }
-                if let Some(err) = &result.error {
-                    self.console.push_str(err);
-                } else {
-                    self.console.push_str(&format!("=> {}", result.value));
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.pending = None;
+                    }
+                });
+            });
+
+        let Some(save) = choice else {
+            return;
+        };
+        for index in tabs {
+            if save {
+                if !self.save(index) {
+                    self.pending = None;
+                    return;
                 }
+            } else if let Some(tab) = self.tabs.get_mut(index) {
+                tab.doc.revert();
+            }
+        }
+        if let Some(action) = self.pending.take() {
+            self.perform(ctx, action);
+        }
+    }
 
-                self.ast_text = format!("{:?}", result.ast);
+    /// Show the API window of the selected example, inserting the call
+    /// template of the clicked entry at the cursor of the active editor.
+    fn show_api_browser(&mut self, ctx: &egui::Context) {
+        let Some(idx) = self.selected.filter(|_| self.show_api) else {
+            return;
+        };
+        // Wait for the live check while the script is edited, so it is not
+        // compiled again on every keystroke.
+        let script = &self.tabs.script().text;
+        if (self.tabs.active() != Tabs::SCRIPT || self.live_check.diagnostics(script).is_some())
+            && let Some(wait) = self.api_browser.update(&self.examples[idx], script)
+        {
+            ctx.request_repaint_after(wait);
+        }
+        let mut chosen = None;
+        egui::Window::new("API")
+            .open(&mut self.show_api)
+            .default_size([520.0, 420.0])
+            .show(ctx, |ui| {
+                chosen = self.api_browser.show(ui);
+            });
+        let active = self.tabs.active();
+        if let Some(template) = chosen
+            && let Some(tab) = self.tabs.get_mut(active)
+        {
+            let path = (active != Tabs::SCRIPT).then(|| tab.doc.path.clone());
+            let id = egui::Id::new("editor").with(&path);
+            editor::insert_at_cursor(ctx, id, &mut tab.doc.text, &template);
+        }
+    }
 
-                let log_path = format!("logs/{}.log", example.id);
-                self.logs = std::fs::read_to_string(log_path).unwrap_or_default();
+    /// Show the Debugger window with the stepping controls and the call stack
+    /// of the paused script.
+    fn show_debugger(&mut self, ctx: &egui::Context) {
+        if !self.show_debugger {
+            return;
+        }
+        let debugging = self.debugging.is_some();
+        let paused = self.pause.is_some();
+        let mut command = None;
+        let mut start = None;
+        let mut stop = false;
+        let mut goto = None;
+        egui::Window::new("Debugger")
+            .open(&mut self.show_debugger)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    if debugging {
+                        for (label, step) in [
+                            ("Continue", DebugCommand::Continue),
+                            ("Step into", DebugCommand::StepInto),
+                            ("Step over", DebugCommand::StepOver),
+                            ("Step out", DebugCommand::StepOut),
+                        ] {
+                            if ui.add_enabled(paused, egui::Button::new(label)).clicked() {
+                                command = Some(step);
+                            }
+                        }
+                        stop = ui.button("Stop").clicked();
+                    } else {
+                        if ui.button("Start").clicked() {
+                            start = Some(false);
+                        }
+                        if ui.button("Step into").clicked() {
+                            start = Some(true);
+                        }
+                    }
+                });
+                ui.toggle_value(&mut self.show_variables, "Variables");
+                ui.separator();
+                let Some(pause) = &self.pause else {
+                    ui.weak(if debugging {
+                        "Running…"
+                    } else {
+                        "Not running. Click the gutter of the editor to set breakpoints."
+                    });
+                    return;
+                };
+                ui.label("Call stack");
+                // The paused function first, then each caller at its call site.
+                let mut entries = vec![(pause.source.clone(), pause.line, pause.column)];
+                entries.extend(pause.call_stack.iter().rev().filter_map(|frame| {
+                    Some((frame.source.clone(), frame.line?, frame.column.unwrap_or(1)))
+                }));
+                let functions = pause
+                    .call_stack
+                    .iter()
+                    .rev()
+                    .map(|frame| frame.function.as_str())
+                    .chain(["<script>"]);
+                for (i, ((source, line, column), function)) in
+                    entries.into_iter().zip(functions).enumerate()
+                {
+                    let mut text = format!("{function}  line {line}");
+                    if let Some(source) = &source {
+                        text.push_str(&format!(" in '{source}'"));
+                    }
+                    let label = egui::RichText::new(text).monospace();
+                    if ui.selectable_label(i == 0, label).clicked() {
+                        goto = Some((source, line, column));
+                    }
+                }
+            });
+        if let Some(stop_on_entry) = start {
+            self.debug_selected(stop_on_entry);
+        }
+        if let Some(command) = command {
+            self.resume(command);
+        }
+        if stop {
+            self.stop_debugging();
+        }
+        if let Some((source, line, column)) = goto {
+            self.reveal(source.as_deref(), line, column);
+        }
+    }
+
+    /// Show the Variables window: the scope of the paused script while
+    /// debugging, and otherwise the scope left by the last run.
+    fn show_variables(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Variables")
+            .open(&mut self.show_variables)
+            .default_size([360.0, 400.0])
+            .show(ctx, |ui| {
+                let variables = match &self.pause {
+                    Some(pause) => {
+                        let function = pause
+                            .call_stack
+                            .last()
+                            .map_or("<script>", |frame| frame.function.as_str());
+                        ui.label(format!("Paused in {function} at line {}", pause.line));
+                        &pause.variables
+                    }
+                    None if self.debugging.is_some() => {
+                        ui.weak("Running… variables are shown when the script pauses.");
+                        return;
+                    }
+                    None => {
+                        ui.label("After the last run");
+                        &self.variables
+                    }
+                };
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        scope_view::show(ui, egui::Id::new("variables"), variables);
+                    });
+            });
+    }
+
+    /// Show the first file that changed on disk while it had unsaved changes,
+    /// with a diff and the choice of which version to keep, or that was
+    /// deleted on disk.
+    fn show_conflict(&mut self, ctx: &egui::Context) {
+        let Some(index) = self.tabs.iter().position(|t| t.conflict.is_some()) else {
+            return;
+        };
+        let Some(tab) = self.tabs.get(index) else {
+            return;
+        };
+        let Some((path, change)) = &tab.conflict else {
+            return;
+        };
+
+        let theirs = match change {
+            DiskChange::Changed(theirs) => theirs,
+            DiskChange::Deleted => {
+                let mut dismissed = false;
+                egui::Window::new("File deleted on disk")
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        ui.label(format!("{} was deleted on disk.", path.display()));
+                        ui.label(
+                            "Your text is kept as unsaved changes; saving writes the file again.",
+                        );
+                        if ui.button("OK").clicked() {
+                            dismissed = true;
+                        }
+                    });
+                if dismissed && let Some(tab) = self.tabs.get_mut(index) {
+                    tab.conflict = None;
+                }
+                return;
+            }
+        };
+        let mut keep_mine = None;
+        egui::Window::new("File changed on disk")
+            .collapsible(false)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} changed on disk while it had unsaved changes.",
+                    path.display()
+                ));
+                ui.label("Lines only on disk are red, lines only in your version are green.");
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        for line in line_diff(theirs, &tab.doc.text) {
+                            let (text, color) = match line {
+                                DiffLine::Same(l) => (format!("  {l}"), ui.visuals().text_color()),
+                                DiffLine::Removed(l) => {
+                                    (format!("- {l}"), egui::Color32::LIGHT_RED)
+                                }
+                                DiffLine::Added(l) => {
+                                    (format!("+ {l}"), egui::Color32::LIGHT_GREEN)
+                                }
+                            };
+                            ui.label(egui::RichText::new(text).monospace().color(color));
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    if ui.button("Keep mine").clicked() {
+                        keep_mine = Some(true);
+                    }
+                    if ui.button("Take theirs").clicked() {
+                        keep_mine = Some(false);
+                    }
+                });
+            });
+
+        let Some(keep_mine) = keep_mine else {
+            return;
+        };
+        if let Some(tab) = self.tabs.get_mut(index)
+            && let Some((path, DiskChange::Changed(theirs))) = tab.conflict.take()
+        {
+            if keep_mine {
+                tab.doc.keep_mine(path, theirs);
+            } else {
+                tab.doc.take_theirs(path, theirs);
             }
         }
+        if index == Tabs::SCRIPT && !keep_mine {
+            self.run_selected();
+        }
+    }
+}
+
+/// List the files under `dir` recursively.
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(list_files(&path));
+        } else {
+            files.push(path);
+        }
     }
+    files
+}
+
+/// Render a clickable link for an error location, returning whether it was clicked.
+fn location_link(ui: &mut egui::Ui, text: String) -> bool {
+    ui.add(egui::Link::new(
+        egui::RichText::new(text)
+            .monospace()
+            .color(egui::Color32::LIGHT_RED),
+    ))
+    .clicked()
 }
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if ctx.input(|i| i.viewport().close_requested())
+            && self.tabs.iter().any(|t| t.doc.is_dirty())
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.pending = Some(Pending::Quit);
+        }
+
+        self.poll_run();
+        self.poll_debug();
+        self.poll_tests();
+        self.poll_optimizations();
+        if self.running.is_some()
+            || self.debugging.is_some()
+            || self.tests_rx.is_some()
+            || self.optimize_rx.is_some()
+        {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+
+        let mut other_files_changed = false;
         while let Ok(event) = self.watch_rx.try_recv() {
-            if event
-                .paths
-                .iter()
-                .any(|p| p.extension().and_then(|s| s.to_str()) == Some("rhai"))
-            {
-                let selected_id = self
-                    .selected
-                    .and_then(|i| self.examples.get(i).map(|e| e.id.clone()));
-                self.examples = ExampleRegistry::all();
-                self.selected =
-                    selected_id.and_then(|id| self.examples.iter().position(|e| e.id == id));
-                if self.selected.is_some() {
-                    self.load_selected_script();
-                    self.run_selected();
-                }
+            if event.paths.iter().any(|p| {
+                matches!(
+                    p.extension().and_then(|s| s.to_str()),
+                    Some("rhai" | "toml")
+                )
+            }) {
+                self.reload_examples();
                 self.reload_notice = Some("Scripts recompiled".to_string());
+            } else {
+                other_files_changed = true;
+                if event.paths.iter().any(|p| self.tabs.find(p).is_some()) {
+                    self.sync_with_disk();
+                }
             }
         }
+        // Reloading the examples already lists the files again.
+        if other_files_changed {
+            self.refresh_files();
+        }
+
+        if !self.manifest_problems.is_empty() {
+            egui::TopBottomPanel::top("manifest_problems").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::LIGHT_RED,
+                        "Problems in the examples manifest",
+                    );
+                    if ui.small_button("Dismiss").clicked() {
+                        self.manifest_problems.clear();
+                    }
+                });
+                for problem in &self.manifest_problems {
+                    ui.label(egui::RichText::new(problem).color(egui::Color32::LIGHT_RED));
+                }
+            });
+        }
 
         // Side panel listing all examples and reload button.
+        let mut select = None;
         egui::SidePanel::left("example_list").show(ctx, |ui| {
             ui.text_edit_singleline(&mut self.filter);
 
             if ui.button("Reload scripts").clicked() {
-                let selected_id = self
-                    .selected
-                    .and_then(|i| self.examples.get(i).map(|e| e.id.clone()));
-                self.examples = ExampleRegistry::all();
-                self.selected =
-                    selected_id.and_then(|id| self.examples.iter().position(|e| e.id == id));
-                if self.selected.is_some() {
-                    self.load_selected_script();
-                    self.run_selected();
-                }
+                self.reload_examples();
             }
 
             ui.separator();
@@ -137,14 +1070,21 @@ impl eframe::App for App {
             let filter = self.filter.to_ascii_lowercase();
             for (i, ex) in self.examples.iter().enumerate() {
                 let name = ex.name.to_ascii_lowercase();
+                let marker = if ex.is_modified() {
+                    MODIFIED_MARKER
+                } else {
+                    ""
+                };
                 if filter.is_empty() || name.contains(&filter) {
                     if filter.is_empty() {
                         if ui
-                            .selectable_label(self.selected == Some(i), &ex.name)
+                            .selectable_label(
+                                self.selected == Some(i),
+                                format!("{}{marker}", ex.name),
+                            )
                             .clicked()
                         {
-                            self.selected = Some(i);
-                            self.loaded_script = None;
+                            select = Some(i);
                         }
                     } else if let Some(pos) = name.find(&filter) {
                         let len = self.filter.len();
@@ -162,19 +1102,22 @@ impl eframe::App for App {
                             egui::text::TextFormat::simple(font.clone(), egui::Color32::YELLOW),
                         );
                         job.append(
-                            &ex.name[pos + len..],
+                            &format!("{}{marker}", &ex.name[pos + len..]),
                             0.0,
                             egui::text::TextFormat::simple(font, color),
                         );
                         if ui.selectable_label(self.selected == Some(i), job).clicked() {
-                            self.selected = Some(i);
-                            self.loaded_script = None;
+                            select = Some(i);
                         }
                     }
                 }
             }
         });
 
+        if let Some(i) = select.filter(|i| self.selected != Some(*i)) {
+            self.request(ctx, Pending::Select(i));
+        }
+
         // Optional log viewer on the right.
         if !self.logs.is_empty() {
             egui::SidePanel::right("logs").show(ctx, |ui| {
@@ -186,12 +1129,46 @@ impl eframe::App for App {
         }
 
         // Console panel at the bottom.
+        let mut goto = None;
         egui::TopBottomPanel::bottom("console").show(ctx, |ui| {
             ui.label("Console");
             egui::ScrollArea::vertical().show(ui, |ui| {
                 ui.monospace(&self.console);
+                if let Some(err) = &self.last_error {
+                    let mut text = format!("{}: {}", err.kind, err.message);
+                    if let Some(source) = &err.source {
+                        text.push_str(&format!(" in '{source}'"));
+                    }
+                    if let Some(line) = err.line {
+                        let column = err.column.unwrap_or(1);
+                        text.push_str(&format!(" (line {line}, column {column})"));
+                        if location_link(ui, text) {
+                            goto = Some((err.source.clone(), line, column));
+                        }
+                    } else {
+                        ui.colored_label(egui::Color32::LIGHT_RED, text);
+                    }
+                    for frame in err.call_stack.iter().rev() {
+                        let mut text = format!("  in call to '{}'", frame.function);
+                        if let Some(source) = &frame.source {
+                            text.push_str(&format!(" in '{source}'"));
+                        }
+                        if let Some(line) = frame.line {
+                            let column = frame.column.unwrap_or(1);
+                            text.push_str(&format!(" (line {line}, column {column})"));
+                            if location_link(ui, text) {
+                                goto = Some((frame.source.clone(), line, column));
+                            }
+                        } else {
+                            ui.monospace(text);
+                        }
+                    }
+                }
             });
         });
+        if let Some((source, line, column)) = goto {
+            self.goto_location(source.as_deref(), line, column);
+        }
 
         // Main central panel with example details.
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -209,50 +1186,374 @@ impl eframe::App for App {
                 if let Some(note) = &ex.note {
                     ui.label(format!("Note: {}", note));
                 }
+                let permissions = if ex.capabilities.is_empty() {
+                    "none".to_string()
+                } else {
+                    ex.capabilities
+                        .iter()
+                        .map(|c| c.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                ui.label(format!("Permissions: {}", permissions));
+                if ex.is_modified() {
+                    ui.label(format!(
+                        "Modified copy of a bundled example, saved in {}",
+                        ex.script_path.display()
+                    ));
+                }
                 // Link to rendered HTML documentation instead of raw Markdown
                 ui.hyperlink_to("Documentation", ex.doc_html_path.to_string_lossy());
 
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    ui.add(
-                        egui::TextEdit::multiline(&mut self.script)
-                            .code_editor()
-                            .desired_rows(20),
-                    );
+                let modified = ex.is_modified();
+                self.refresh_links();
+                let mut open = None;
+                let mut close = None;
+                ui.horizontal_wrapped(|ui| {
+                    for (i, tab) in self.tabs.iter().enumerate() {
+                        let mut title = tab.title();
+                        if tab.doc.is_dirty() {
+                            title.push_str(DIRTY_MARKER);
+                        }
+                        if tab.conflict.is_some() {
+                            title.push_str(" !");
+                        }
+                        let active = self.tabs.active() == i;
+                        if ui
+                            .selectable_label(active, title)
+                            .on_hover_text(tab.doc.path.display().to_string())
+                            .clicked()
+                        {
+                            open = Some(tab.doc.path.clone());
+                        }
+                        if i != Tabs::SCRIPT && ui.small_button("x").clicked() {
+                            close = Some(i);
+                        }
+                    }
+                    ui.menu_button("Open file", |ui| {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            for (name, path) in &self.files {
+                                if ui.button(name).clicked() {
+                                    open = Some(path.clone());
+                                    ui.close();
+                                }
+                            }
+                        });
+                    });
                 });
+                if !self.links.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Follow:");
+                        for (name, path) in &self.links {
+                            if ui.link(name).clicked() {
+                                open = Some(path.clone());
+                            }
+                        }
+                    });
+                }
+                if let Some(path) = open {
+                    match self.tabs.find(&path) {
+                        Some(i) => self.tabs.set_active(i),
+                        None => {
+                            self.open_file(path);
+                        }
+                    }
+                }
+                if let Some(i) = close {
+                    self.request(ctx, Pending::CloseTab(i));
+                }
+
+                let active = self.tabs.active();
+                let jump = self.jump_to.take();
+                let mut breakpoints_changed = false;
+                match self.tabs.get_mut(active) {
+                    // Other files are edited as plain text, without checks or breakpoints.
+                    Some(tab) if !tab.is_script() => {
+                        let path = Some(tab.doc.path.clone());
+                        let id = egui::Id::new("editor").with(&path);
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            editor::show(ui, id, &mut tab.doc.text, None, jump);
+                        });
+                    }
+                    Some(tab) => {
+                        if let Some(wait) =
+                            self.live_check.update(&self.examples[idx], &tab.doc.text)
+                        {
+                            ctx.request_repaint_after(wait);
+                        }
+                        let diagnostics = self
+                            .live_check
+                            .diagnostics(&tab.doc.text)
+                            .unwrap_or_default()
+                            .to_vec();
+                        let path = (active != Tabs::SCRIPT).then(|| tab.doc.path.clone());
+                        let line_in_tab = |location: &Option<Location>| {
+                            location.as_ref().filter(|l| l.path == path).map(|l| l.line)
+                        };
+                        let mut marks = LineMarks {
+                            error: line_in_tab(&self.error_location),
+                            paused: line_in_tab(&self.pause_location),
+                            breakpoints: self.breakpoints.get(&path).cloned().unwrap_or_default(),
+                        };
+                        let id = egui::Id::new("editor").with(&path);
+                        let completions: Vec<&Completion> = self
+                            .live_check
+                            .functions()
+                            .iter()
+                            .chain(&self.completions)
+                            .collect();
+                        let script = editor::Script {
+                            marks: &mut marks,
+                            diagnostics: &diagnostics,
+                            completions: &completions,
+                        };
+                        let output = egui::ScrollArea::vertical()
+                            .show(ui, |ui| {
+                                editor::show(ui, id, &mut tab.doc.text, Some(script), jump)
+                            })
+                            .inner;
+                        if self.breakpoints.get(&path) != Some(&marks.breakpoints) {
+                            breakpoints_changed = true;
+                            self.breakpoints.insert(path, marks.breakpoints);
+                        }
+                        if self.show_ast {
+                            let cursor = output.cursor_range.map(|r| r.primary.index);
+                            self.ast_view.follow_cursor(
+                                self.live_check.tree(),
+                                &tab.doc.text,
+                                cursor,
+                            );
+                        }
+                        let status = self.live_check.status(&tab.doc.text);
+                        let color = match self.live_check.diagnostics(&tab.doc.text) {
+                            Some([]) => egui::Color32::LIGHT_GREEN,
+                            Some(_) => egui::Color32::LIGHT_RED,
+                            None => ui.visuals().weak_text_color(),
+                        };
+                        ui.colored_label(color, status);
+                    }
+                    None => {}
+                }
 
-                let script_path = ex.script_path.clone();
+                if breakpoints_changed && let Some(session) = &self.debugging {
+                    session.set_breakpoints(self.breakpoint_list());
+                }
+
+                let dirty = self.is_dirty(active);
+                let unsaved = self.tabs.iter().any(|t| t.doc.is_dirty());
+                let mut save = false;
+                let mut revert = false;
+                let mut reset = false;
                 ui.horizontal(|ui| {
-                    if ui.button("Run").clicked() {
+                    let running = self.running.is_some() || self.debugging.is_some();
+                    if ui.add_enabled(!running, egui::Button::new("Run")).clicked() {
                         self.run_selected();
                     }
-                    if ui.button("Save").clicked() {
-                        let _ = std::fs::write(&script_path, &self.script);
+                    if ui
+                        .add_enabled(!running, egui::Button::new("Debug"))
+                        .on_hover_text("Run until a breakpoint; click the gutter to set one")
+                        .clicked()
+                    {
+                        self.debug_selected(false);
+                    }
+                    if ui.add_enabled(running, egui::Button::new("Stop")).clicked() {
+                        if let Some(handle) = &self.running {
+                            handle.cancel();
+                        }
+                        self.stop_debugging();
+                    }
+                    save = ui.add_enabled(dirty, egui::Button::new("Save")).clicked();
+                    revert = ui.add_enabled(dirty, egui::Button::new("Revert")).clicked();
+                    reset = ui
+                        .add_enabled(modified, egui::Button::new("Reset to original"))
+                        .clicked();
+                    let testing = self.tests_rx.is_some();
+                    if ui
+                        .add_enabled(!testing, egui::Button::new("Run tests"))
+                        .clicked()
+                    {
+                        self.run_selected_tests();
                     }
                     if ui.button("Show AST").clicked() {
                         self.show_ast = true;
                     }
+                    if ui.button("Variables").clicked() {
+                        self.show_variables = true;
+                    }
+                    let comparing = self.optimize_rx.is_some();
+                    if ui
+                        .add_enabled(!comparing, egui::Button::new("Compare optimizations"))
+                        .clicked()
+                    {
+                        self.compare_optimizations();
+                    }
+                    if ui.button("API").clicked() {
+                        self.show_api = true;
+                    }
+                    if unsaved {
+                        ui.label(format!("Unsaved changes{DIRTY_MARKER}"));
+                    }
+                    if let Some(handle) = &self.running {
+                        ui.spinner();
+                        ui.label(format!("Running… {:.1}s", handle.elapsed().as_secs_f32()));
+                    } else if self.pause.is_some() {
+                        ui.label("Paused");
+                    } else if let Some(session) = &self.debugging {
+                        ui.spinner();
+                        let elapsed = session.run().elapsed();
+                        ui.label(format!("Debugging… {:.1}s", elapsed.as_secs_f32()));
+                    } else if let Some(elapsed) = self.last_elapsed {
+                        ui.label(format!("Finished in {:.1}s", elapsed.as_secs_f32()));
+                    }
                 });
+                if save {
+                    self.save(active);
+                }
+                if revert && let Some(tab) = self.tabs.get_mut(active) {
+                    tab.doc.revert();
+                }
+                if reset {
+                    self.request(ctx, Pending::ResetToOriginal);
+                }
             } else {
                 ui.label("Select an example from the left");
             }
         });
 
+        if self.show_tests {
+            let mut goto = None;
+            egui::Window::new("Tests")
+                .open(&mut self.show_tests)
+                .show(ctx, |ui| {
+                    if self.tests_rx.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Running tests…");
+                        });
+                    }
+                    let Some(report) = &self.test_report else {
+                        return;
+                    };
+                    ui.label(format!(
+                        "{} of {} passed",
+                        report.passed(),
+                        report.cases.len()
+                    ));
+                    for err in &report.errors {
+                        ui.colored_label(egui::Color32::LIGHT_RED, err.to_string());
+                    }
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        for case in &report.cases {
+                            let (mark, color, err) = match &case.outcome {
+                                TestOutcome::Passed => ("✔", egui::Color32::LIGHT_GREEN, None),
+                                TestOutcome::Failed(e) => ("✘", egui::Color32::LIGHT_RED, Some(e)),
+                                TestOutcome::Error(e) => ("⚠", egui::Color32::YELLOW, Some(e)),
+                            };
+                            let mut text = format!("{mark} {}", case.name);
+                            if let Some(module) = &case.module {
+                                text.push_str(&format!(" ({module})"));
+                            }
+                            text.push_str(&format!(
+                                "  {:.2} ms",
+                                case.duration.as_secs_f64() * 1000.0
+                            ));
+                            ui.colored_label(color, text);
+                            if let Some(err) = err {
+                                let text = format!("    {}: {}", err.kind, err.message);
+                                match err.line {
+                                    Some(line) => {
+                                        if location_link(ui, text) {
+                                            goto = Some((
+                                                err.source.clone(),
+                                                line,
+                                                err.column.unwrap_or(1),
+                                            ));
+                                        }
+                                    }
+                                    None => {
+                                        ui.monospace(text);
+                                    }
+                                }
+                            }
+                        }
+                    });
+                });
+            if let Some((source, line, column)) = goto {
+                self.goto_location(source.as_deref(), line, column);
+            }
+        }
+
+        if self.show_optimize {
+            egui::Window::new("Optimization levels")
+                .open(&mut self.show_optimize)
+                .default_size([900.0, 500.0])
+                .show(ctx, |ui| {
+                    if self.optimize_rx.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Compiling and running…");
+                        });
+                    }
+                    optimize_view::show(ui, &self.level_runs);
+                });
+        }
+
+        self.show_debugger(ctx);
+        self.show_variables(ctx);
+        self.show_api_browser(ctx);
+        self.show_pending_prompt(ctx);
+        self.show_conflict(ctx);
+
         if self.show_ast {
             egui::Window::new("AST")
                 .open(&mut self.show_ast)
                 .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        ui.monospace(&self.ast_text);
-                    });
-                    if ui.button("Export AST").clicked() {
-                        if let Some(idx) = self.selected {
+                    let tab = self.tabs.get(self.tabs.active());
+                    if tab.is_some_and(|t| !t.is_script()) {
+                        ui.weak("The active tab is not a Rhai script.");
+                        return;
+                    }
+                    let text = tab.map_or("", |t| t.doc.text.as_str());
+                    if self.live_check.diagnostics(text) != Some(&[]) {
+                        ui.weak("Showing the last version that compiled.");
+                    }
+                    egui::ScrollArea::vertical()
+                        .auto_shrink([false, true])
+                        .max_height(ui.available_height() - 30.0)
+                        .show(ui, |ui| {
+                            let tree = self.live_check.tree();
+                            if let Some(range) = self.ast_view.show(ui, tree, text) {
+                                self.jump_to = Some(range);
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        let json = self.live_check.ast_json();
+                        let compiled = !json.is_empty();
+                        let export = ui
+                            .add_enabled(compiled, egui::Button::new("Export AST"))
+                            .on_hover_text("Write the AST shown above to logs/<id>.ast.json");
+                        if export.clicked()
+                            && let Some(idx) = self.selected
+                        {
                             let id = &self.examples[idx].id;
-                            let log_dir = std::path::Path::new("logs");
-                            let _ = std::fs::create_dir_all(log_dir);
-                            let path = log_dir.join(format!("{}.ast", id));
-                            let _ = std::fs::write(path, &self.ast_text);
+                            let log_dir = self.workspace.logs_dir();
+                            let path = log_dir.join(format!("{id}.ast.json"));
+                            self.ast_status = match std::fs::create_dir_all(&log_dir)
+                                .and_then(|()| std::fs::write(&path, json))
+                            {
+                                Ok(()) => format!("Exported to {}", path.display()),
+                                Err(e) => format!("Export failed: {e}"),
+                            };
                         }
-                    }
+                        if ui
+                            .add_enabled(compiled, egui::Button::new("Copy JSON"))
+                            .clicked()
+                        {
+                            ui.ctx().copy_text(json.to_string());
+                            self.ast_status = "Copied to the clipboard".to_string();
+                        }
+                        ui.label(&self.ast_status);
+                    });
                 });
         }
     }
@@ -0,0 +1,180 @@
+//! Collapsible tree of a script's syntax, linked to the editor.
+
+use super::editor::char_index;
+use crate::examples::AstNode;
+use eframe::egui;
+use egui::collapsing_header::CollapsingState;
+use std::ops::Range;
+
+/// Index of the end of the token starting at `start` in `chars`: a word or
+/// number, a quoted string or a single character.
+fn token_end(chars: &[char], start: usize) -> usize {
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_' || *c == '.';
+    match chars.get(start) {
+        Some(&quote @ ('"' | '\'' | '`')) => {
+            let mut i = start + 1;
+            while i < chars.len() && chars[i] != quote {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            (i + 1).min(chars.len())
+        }
+        Some(c) if is_word(c) => start + chars[start..].iter().take_while(|c| is_word(c)).count(),
+        Some(_) => start + 1,
+        None => start,
+    }
+}
+
+/// Characters of `chars` covered by `node`: from the first position in its
+/// subtree to the end of the token at the last one.
+///
+/// Rhai only records where nodes start, so closing brackets after the last
+/// nested node are not included.
+pub fn node_chars(chars: &[char], text: &str, node: &AstNode) -> Option<Range<usize>> {
+    let start = match (node.line, node.column) {
+        (Some(line), Some(column)) => {
+            let start = char_index(text, line, column);
+            Some(start..token_end(chars, start))
+        }
+        _ => None,
+    };
+    node.children
+        .iter()
+        .filter_map(|child| node_chars(chars, text, child))
+        .chain(start)
+        .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+}
+
+/// Path of child indices to the innermost node of `tree` covering `cursor`.
+pub fn enclosing_path(tree: &[AstNode], text: &str, cursor: usize) -> Vec<usize> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut path = Vec::new();
+    let mut nodes = tree;
+    while let Some(i) = nodes.iter().position(|n| {
+        node_chars(&chars, text, n).is_some_and(|r| r.start <= cursor && cursor < r.end)
+    }) {
+        path.push(i);
+        nodes = &nodes[i].children;
+    }
+    path
+}
+
+/// Selection and expansion state of the AST window.
+#[derive(Default)]
+pub struct AstView {
+    /// Path of the selected node.
+    selected: Vec<usize>,
+    /// Whether to expand the parents of the selected node and scroll to it.
+    reveal: bool,
+    /// Editor cursor seen last, to notice when it moves.
+    cursor: Option<usize>,
+}
+
+impl AstView {
+    /// Select the node enclosing the editor `cursor` when it has moved.
+    pub fn follow_cursor(&mut self, tree: &[AstNode], text: &str, cursor: Option<usize>) {
+        if cursor == self.cursor {
+            return;
+        }
+        self.cursor = cursor;
+        if let Some(cursor) = cursor {
+            self.selected = enclosing_path(tree, text, cursor);
+            self.reveal = !self.selected.is_empty();
+        }
+    }
+
+    /// Show `tree` as collapsible nodes with their kind, label and position.
+    ///
+    /// Returns the characters of `text` covered by the node the user clicked.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        tree: &[AstNode],
+        text: &str,
+    ) -> Option<Range<usize>> {
+        let mut clicked = None;
+        let mut path = Vec::new();
+        self.show_nodes(ui, tree, &mut path, &mut clicked);
+        self.reveal = false;
+        let node = clicked?;
+        self.selected = node;
+        let mut nodes = tree;
+        let mut found = None;
+        for &i in &self.selected {
+            found = nodes.get(i);
+            nodes = found.map_or(&[], |n| &n.children[..]);
+        }
+        let chars: Vec<char> = text.chars().collect();
+        node_chars(&chars, text, found?)
+    }
+
+    fn show_nodes(
+        &mut self,
+        ui: &mut egui::Ui,
+        nodes: &[AstNode],
+        path: &mut Vec<usize>,
+        clicked: &mut Option<Vec<usize>>,
+    ) {
+        for (i, node) in nodes.iter().enumerate() {
+            path.push(i);
+            let mut text = node.summary();
+            if let (Some(line), Some(column)) = (node.line, node.column) {
+                text.push_str(&format!("  {line}:{column}"));
+            }
+            let selected = *path == self.selected;
+            let mut label = |ui: &mut egui::Ui| {
+                let response =
+                    ui.selectable_label(selected, egui::RichText::new(&text).monospace());
+                if response.clicked() {
+                    *clicked = Some(path.clone());
+                }
+                if selected && self.reveal {
+                    response.scroll_to_me(Some(egui::Align::Center));
+                }
+            };
+            if node.children.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.add_space(ui.spacing().indent);
+                    label(ui);
+                });
+            } else {
+                let id = ui.make_persistent_id(("ast", &path[..]));
+                let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                if self.reveal && self.selected.starts_with(path) && !selected {
+                    state.set_open(true);
+                }
+                state
+                    .show_header(ui, label)
+                    .body(|ui| self.show_nodes(ui, &node.children, path, clicked));
+            }
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::{Literal, syntax_tree};
+    use Rhai_Learning::host::HostEngine;
+
+    #[test]
+    fn cursor_selects_the_innermost_enclosing_node() {
+        let text = "let x = 1;\nprint(x + 20);";
+        let ast = HostEngine::builder()
+            .build()
+            .engine()
+            .compile(text)
+            .unwrap();
+        let tree = syntax_tree(&ast);
+        let chars: Vec<char> = text.chars().collect();
+
+        // On `20` inside `print(x + 20)`.
+        let path = enclosing_path(&tree, text, 22);
+        assert_eq!(path, [1, 0, 1]);
+        let literal = &tree[1].children[0].children[1];
+        assert_eq!(literal.value, Some(Literal::Int(20)));
+        assert_eq!(node_chars(&chars, text, literal), Some(21..23));
+        assert_eq!(node_chars(&chars, text, &tree[1]), Some(11..23));
+        assert!(enclosing_path(&tree, text, 10).is_empty());
+    }
+}
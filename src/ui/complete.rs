@@ -0,0 +1,469 @@
+//! Autocompletion of function and variable names in the code editor.
+
+use super::highlight::{self, TokenKind};
+use Rhai_Learning::host::{FnInfo, FnOrigin};
+use eframe::egui;
+use egui::text::{CCursor, CCursorRange, LayoutJob, TextFormat};
+use egui::text_edit::{TextEditOutput, TextEditState};
+use regex::{Captures, Regex};
+use rhai::AST;
+use std::sync::OnceLock;
+
+/// Most completions shown at once.
+const MAX_SHOWN: usize = 12;
+
+/// Where a completion comes from, in the order they are listed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompletionKind {
+    /// A variable, constant or parameter declared before the cursor.
+    Variable,
+    /// A function defined in the script.
+    Script,
+    /// A function registered by the host.
+    Host,
+    /// A function of Rhai's standard packages.
+    Builtin,
+}
+
+/// A name that can be inserted at the cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    /// Name matched against the word being typed.
+    pub name: String,
+    /// Parameters and return type, or what kind of variable it is.
+    pub detail: String,
+    /// Every signature of the name, shown on hover.
+    pub signatures: Vec<String>,
+    /// Text inserted in place of the word being typed.
+    pub insert: String,
+    /// Where the completion comes from.
+    pub kind: CompletionKind,
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Completions for the functions in `functions`, one per name and origin
+/// with the overloads listed in its signatures.
+pub fn function_completions(functions: &[FnInfo]) -> Vec<Completion> {
+    let mut completions: Vec<Completion> = Vec::new();
+    for f in functions.iter().filter(|f| is_identifier(&f.name)) {
+        let kind = match f.origin {
+            FnOrigin::Script => CompletionKind::Script,
+            FnOrigin::Host => CompletionKind::Host,
+            FnOrigin::Builtin => CompletionKind::Builtin,
+        };
+        let insert = if f.params.is_empty() {
+            format!("{}()", f.name)
+        } else {
+            format!("{}(", f.name)
+        };
+        match completions
+            .iter_mut()
+            .find(|c| c.name == f.name && c.kind == kind)
+        {
+            Some(c) => {
+                c.signatures.push(f.signature.clone());
+                if f.params.is_empty() {
+                    c.insert = insert;
+                }
+            }
+            None => {
+                let mut detail = format!("({})", f.param_list());
+                if !f.return_type.is_empty() {
+                    detail.push_str(&format!(" -> {}", f.return_type));
+                }
+                completions.push(Completion {
+                    name: f.name.clone(),
+                    detail,
+                    signatures: vec![f.signature.clone()],
+                    insert,
+                    kind,
+                });
+            }
+        }
+    }
+    for c in &mut completions {
+        if c.signatures.len() > 1 {
+            c.detail
+                .push_str(&format!("  +{} overloads", c.signatures.len() - 1));
+        }
+    }
+    completions
+}
+
+/// Completions for the functions defined in `ast`.
+pub fn script_completions(ast: &AST) -> Vec<Completion> {
+    ast.iter_functions()
+        .map(|f| {
+            let params = f.params.join(", ");
+            let insert = if f.params.is_empty() {
+                format!("{}()", f.name)
+            } else {
+                format!("{}(", f.name)
+            };
+            Completion {
+                name: f.name.to_string(),
+                detail: format!("({params})"),
+                signatures: vec![format!("fn {}({params})", f.name)],
+                insert,
+                kind: CompletionKind::Script,
+            }
+        })
+        .collect()
+}
+
+/// Names declared in one `{}` block, or at the top level of the script.
+#[derive(Default)]
+struct Block {
+    /// Names with the detail shown for them, in declaration order.
+    names: Vec<(String, &'static str)>,
+    /// Whether this is the body of a function, which cannot see the variables
+    /// outside it, only the constants.
+    function: bool,
+}
+
+/// A declaration or brace found while tracking which blocks are open.
+enum Token<'a> {
+    Decl(Captures<'a>),
+    /// `{` or `}`.
+    Brace(u8),
+}
+
+/// Completions for the variables, constants, loop variables and function
+/// parameters in scope at byte offset `before` of `text`.
+///
+/// Declarations in a block closed before `before` are left out. Loop variables
+/// and parameters belong to the block that follows them.
+pub fn variable_completions(text: &str, before: usize) -> Vec<Completion> {
+    static DECLARATION: OnceLock<Regex> = OnceLock::new();
+    let declaration = DECLARATION.get_or_init(|| {
+        Regex::new(
+            r"\b(let|const)\s+([A-Za-z_]\w*)|\bfor\s*\(?\s*([A-Za-z_]\w*)(?:\s*,\s*([A-Za-z_]\w*))?\s*\)?\s+in\b|\bfn\s+[A-Za-z_]\w*\s*\(([^)]*)\)",
+        )
+        .expect("valid regex")
+    });
+    let text = &text[..before];
+    let spans = highlight::tokenize(text, &[]);
+    let in_code = |pos: usize| {
+        spans
+            .iter()
+            .find(|(range, _)| range.contains(&pos))
+            .is_none_or(|(_, kind)| !matches!(kind, TokenKind::String | TokenKind::Comment))
+    };
+
+    // Declarations and braces in source order.
+    let mut tokens: Vec<(usize, Token)> = declaration
+        .captures_iter(text)
+        .filter(|caps| in_code(caps.get(0).map_or(0, |m| m.start())))
+        .map(|caps| (caps.get(0).map_or(0, |m| m.start()), Token::Decl(caps)))
+        .collect();
+    for (range, kind) in &spans {
+        if *kind == TokenKind::Plain {
+            for (i, c) in text[range.clone()].bytes().enumerate() {
+                if c == b'{' || c == b'}' {
+                    tokens.push((range.start + i, Token::Brace(c)));
+                }
+            }
+        }
+    }
+    tokens.sort_by_key(|(pos, _)| *pos);
+
+    let mut blocks = vec![Block::default()];
+    // Loop variables or parameters waiting for the block they belong to.
+    let mut pending = Block::default();
+    for (pos, token) in tokens {
+        match token {
+            Token::Decl(caps) => {
+                if let (Some(keyword), Some(name)) = (caps.get(1), caps.get(2)) {
+                    let detail = if keyword.as_str() == "let" {
+                        "variable"
+                    } else {
+                        "constant"
+                    };
+                    if let Some(block) = blocks.last_mut() {
+                        block.names.push((name.as_str().to_string(), detail));
+                    }
+                }
+                for name in [caps.get(3), caps.get(4)].into_iter().flatten() {
+                    pending
+                        .names
+                        .push((name.as_str().to_string(), "loop variable"));
+                }
+                if let Some(params) = caps.get(5) {
+                    pending.function = true;
+                    for param in params.as_str().split(',') {
+                        pending.names.push((param.trim().to_string(), "parameter"));
+                    }
+                }
+            }
+            // The `#{` of a map literal does not start a block of statements.
+            Token::Brace(b'{') if pos > 0 && text.as_bytes()[pos - 1] == b'#' => {
+                blocks.push(Block::default());
+            }
+            Token::Brace(b'{') => blocks.push(std::mem::take(&mut pending)),
+            Token::Brace(_) => {
+                if blocks.len() > 1 {
+                    blocks.pop();
+                }
+            }
+        }
+    }
+
+    // Outside the innermost function body only constants are visible.
+    let body = blocks.iter().rposition(|b| b.function).unwrap_or(0);
+    let mut completions: Vec<Completion> = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        for (name, detail) in &block.names {
+            if !is_identifier(name) || (i < body && *detail != "constant") {
+                continue;
+            }
+            // A later declaration shadows an earlier one of the same name.
+            completions.retain(|c| c.name != *name);
+            completions.push(Completion {
+                name: name.clone(),
+                detail: detail.to_string(),
+                signatures: Vec::new(),
+                insert: name.clone(),
+                kind: CompletionKind::Variable,
+            });
+        }
+    }
+    completions
+}
+
+/// Completions starting with `word`, best first.
+fn matching<'a>(
+    completions: impl Iterator<Item = &'a Completion>,
+    word: &str,
+) -> Vec<&'a Completion> {
+    let mut matches: Vec<&Completion> = completions
+        .filter(|c| c.name.starts_with(word) && c.name != word)
+        .collect();
+    matches.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
+    matches.dedup_by(|a, b| a.name == b.name);
+    matches.truncate(MAX_SHOWN);
+    matches
+}
+
+/// State of the completion popup of one editor, kept between frames.
+#[derive(Clone, Default)]
+struct Popup {
+    open: bool,
+    selected: usize,
+    count: usize,
+    word: String,
+    rect: Option<egui::Rect>,
+}
+
+fn popup_id(id: egui::Id) -> egui::Id {
+    id.with("completion")
+}
+
+/// Take the keys an open popup reacts to before the editor `id` sees them,
+/// returning whether the selected completion should be inserted.
+pub fn handle_keys(ui: &egui::Ui, id: egui::Id) -> bool {
+    let mut popup: Popup = ui.data(|d| d.get_temp(popup_id(id))).unwrap_or_default();
+    if !popup.open || popup.count == 0 || !ui.memory(|m| m.has_focus(id)) {
+        return false;
+    }
+    let key = |key| ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+    if key(egui::Key::ArrowDown) {
+        popup.selected = (popup.selected + 1) % popup.count;
+    }
+    if key(egui::Key::ArrowUp) {
+        popup.selected = (popup.selected + popup.count - 1) % popup.count;
+    }
+    if key(egui::Key::Escape) {
+        popup.open = false;
+    }
+    let accept = popup.open && (key(egui::Key::Enter) || key(egui::Key::Tab));
+    ui.data_mut(|d| d.insert_temp(popup_id(id), popup));
+    accept
+}
+
+/// Show the completions for the word before the cursor of the editor `id`,
+/// inserting the chosen one into `text`.
+///
+/// `accept` inserts the selected completion, as returned by [`handle_keys`].
+pub fn show(
+    ui: &egui::Ui,
+    id: egui::Id,
+    text: &mut String,
+    output: &TextEditOutput,
+    completions: &[&Completion],
+    accept: bool,
+) {
+    let ctx = ui.ctx();
+    let mut popup: Popup = ui.data(|d| d.get_temp(popup_id(id))).unwrap_or_default();
+    let Some(cursor) = output.cursor_range.map(|r| r.primary) else {
+        ui.data_mut(|d| d.remove::<Popup>(popup_id(id)));
+        return;
+    };
+
+    let end = text
+        .char_indices()
+        .nth(cursor.index)
+        .map_or(text.len(), |(i, _)| i);
+    let start = text[..end]
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let word = text[start..end].to_string();
+    if output.response.changed() {
+        popup.open = is_identifier(&word) && !in_string_or_comment(ctx, text, start);
+        popup.selected = 0;
+    } else if word != popup.word {
+        popup.open = false;
+    } else if output.response.lost_focus() {
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        let on_popup = popup.rect.zip(pointer).is_some_and(|(r, p)| r.contains(p));
+        popup.open &= on_popup;
+    }
+    popup.word = word.clone();
+
+    let variables = if popup.open {
+        variable_completions(text, start)
+    } else {
+        Vec::new()
+    };
+    let matches = if popup.open {
+        matching(variables.iter().chain(completions.iter().copied()), &word)
+    } else {
+        Vec::new()
+    };
+    popup.count = matches.len();
+    popup.selected = popup.selected.min(matches.len().saturating_sub(1));
+
+    let mut chosen = accept.then_some(popup.selected);
+    popup.rect = None;
+    if !matches.is_empty() {
+        let pos = output
+            .galley
+            .pos_from_cursor(cursor)
+            .translate(output.galley_pos.to_vec2())
+            .left_bottom();
+        let font = egui::TextStyle::Monospace.resolve(ui.style());
+        let response = egui::Area::new(popup_id(id).with("area"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    for (i, c) in matches.iter().enumerate() {
+                        let mut job = LayoutJob::default();
+                        let color = ui.visuals().strong_text_color();
+                        job.append(&c.name, 0.0, TextFormat::simple(font.clone(), color));
+                        let color = ui.visuals().weak_text_color();
+                        job.append(&c.detail, 8.0, TextFormat::simple(font.clone(), color));
+                        let mut item = ui.selectable_label(i == popup.selected, job);
+                        if !c.signatures.is_empty() {
+                            item = item.on_hover_text(c.signatures.join("\n"));
+                        }
+                        if item.clicked() {
+                            chosen = Some(i);
+                        }
+                    }
+                });
+            });
+        popup.rect = Some(response.response.rect);
+    }
+
+    if let Some(c) = chosen.and_then(|i| matches.get(i)) {
+        let insert = c.insert.clone();
+        text.replace_range(start..end, &insert);
+        let cursor = text[..start].chars().count() + insert.chars().count();
+        let mut state = TextEditState::load(ctx, id).unwrap_or_default();
+        state
+            .cursor
+            .set_char_range(Some(CCursorRange::one(CCursor::new(cursor))));
+        state.store(ctx, id);
+        ui.memory_mut(|m| m.request_focus(id));
+        ctx.request_repaint();
+        popup.open = false;
+    }
+    ui.data_mut(|d| d.insert_temp(popup_id(id), popup));
+}
+
+/// Whether byte offset `pos` of `text` is inside a string literal or a comment.
+fn in_string_or_comment(ctx: &egui::Context, text: &str, pos: usize) -> bool {
+    highlight::highlight(ctx, text)
+        .iter()
+        .find(|(range, _)| range.start < pos && pos <= range.end)
+        .is_some_and(|(_, kind)| matches!(kind, TokenKind::String | TokenKind::Comment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables_in_scope_at_the_cursor_are_offered() {
+        let text = "const LIMIT = 3;\nlet hidden = 0;\nfn add(a, b) { let sum = a + b; sum }\nfor (item, i) in [] { let inner = #{ x: 1 }; }\nlet total = 0; // let note = 1;\nfn scale(x) { let y = \"{\"; if x > 0 { let z = 1; } y + CURSOR }\nlet later = 1;";
+        let names = |at: &str| -> Vec<(String, String)> {
+            variable_completions(text, text.find(at).unwrap())
+                .into_iter()
+                .map(|c| (c.name, c.detail))
+                .collect()
+        };
+        let expected = |list: &[(&str, &str)]| -> Vec<(String, String)> {
+            list.iter()
+                .map(|(n, d)| (n.to_string(), d.to_string()))
+                .collect()
+        };
+
+        assert_eq!(
+            names("later"),
+            expected(&[
+                ("LIMIT", "constant"),
+                ("hidden", "variable"),
+                ("total", "variable"),
+            ])
+        );
+        assert_eq!(
+            names("sum }"),
+            expected(&[
+                ("LIMIT", "constant"),
+                ("a", "parameter"),
+                ("b", "parameter"),
+                ("sum", "variable"),
+            ])
+        );
+        assert_eq!(
+            names("; }\nlet total"),
+            expected(&[
+                ("LIMIT", "constant"),
+                ("hidden", "variable"),
+                ("item", "loop variable"),
+                ("i", "loop variable"),
+                ("inner", "variable"),
+            ])
+        );
+        assert_eq!(
+            names("CURSOR"),
+            expected(&[("LIMIT", "constant"), ("x", "parameter"), ("y", "variable"),])
+        );
+    }
+
+    #[test]
+    fn matches_list_variables_before_functions() {
+        let completion = |name: &str, kind| Completion {
+            name: name.to_string(),
+            detail: String::new(),
+            signatures: Vec::new(),
+            insert: name.to_string(),
+            kind,
+        };
+        let completions = [
+            completion("rand_int", CompletionKind::Host),
+            completion("range", CompletionKind::Builtin),
+            completion("rate", CompletionKind::Variable),
+            completion("ra", CompletionKind::Variable),
+        ];
+        let names: Vec<&str> = matching(completions.iter(), "ra")
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names, ["rate", "rand_int", "range"]);
+    }
+}
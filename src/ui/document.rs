@@ -0,0 +1,187 @@
+//! Files open in the editor and their unsaved changes.
+
+use similar::{ChangeTag, TextDiff};
+use std::path::{Path, PathBuf};
+
+/// Text of a file open in the editor together with the contents last read
+/// from or written to disk.
+#[derive(Default)]
+pub struct Document {
+    /// File the text is saved to.
+    pub path: PathBuf,
+    /// Text being edited.
+    pub text: String,
+    saved: String,
+    /// Whether the file was deleted on disk, so the text is unsaved whatever
+    /// it is.
+    deleted: bool,
+}
+
+/// How a document's file changed outside the editor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiskChange {
+    /// The file now holds this text.
+    Changed(String),
+    /// The file no longer exists.
+    Deleted,
+}
+
+impl Document {
+    /// Open `path`, treating a missing file as empty.
+    pub fn open(path: PathBuf) -> Self {
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        Self::with_text(path, text)
+    }
+
+    /// Create a document for `path` whose saved contents are `text`.
+    pub fn with_text(path: PathBuf, text: String) -> Self {
+        Self {
+            path,
+            saved: text.clone(),
+            text,
+            deleted: false,
+        }
+    }
+
+    /// Whether the text differs from the saved contents.
+    pub fn is_dirty(&self) -> bool {
+        self.deleted || self.text != self.saved
+    }
+
+    /// Write the text to the document's file.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        std::fs::write(&self.path, &self.text)?;
+        self.saved = self.text.clone();
+        self.deleted = false;
+        Ok(())
+    }
+
+    /// Record that the text was saved to `path` by other means.
+    pub fn mark_saved(&mut self, path: PathBuf) {
+        self.path = path;
+        self.saved = self.text.clone();
+        self.deleted = false;
+    }
+
+    /// Drop unsaved changes.
+    pub fn revert(&mut self) {
+        self.text = self.saved.clone();
+    }
+
+    /// Read `path` and describe how it differs from what the document was
+    /// loaded from, i.e. the file was changed or deleted elsewhere or the
+    /// document now belongs to a different file.
+    ///
+    /// A file that exists but cannot be read is left alone.
+    pub fn disk_change(&self, path: &Path) -> Option<DiskChange> {
+        let moved = path != self.path;
+        match std::fs::read_to_string(path) {
+            Ok(theirs) => (moved || self.deleted || theirs != self.saved)
+                .then_some(DiskChange::Changed(theirs)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                (moved || !self.deleted).then_some(DiskChange::Deleted)
+            }
+            Err(_) => None,
+        }
+    }
+
+    /// Keep the text after the file at `path` was deleted, as unsaved changes
+    /// that recreate it on the next save.
+    pub fn mark_deleted(&mut self, path: PathBuf) {
+        self.path = path;
+        self.deleted = true;
+    }
+
+    /// Replace the text with the version `theirs` found at `path`.
+    pub fn take_theirs(&mut self, path: PathBuf, theirs: String) {
+        *self = Self::with_text(path, theirs);
+    }
+
+    /// Keep the edited text, treating `theirs` at `path` as the saved version
+    /// so the edits overwrite it on the next save.
+    pub fn keep_mine(&mut self, path: PathBuf, theirs: String) {
+        self.path = path;
+        self.saved = theirs;
+        self.deleted = false;
+    }
+}
+
+/// One line of a diff between two texts.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    /// The line is present in both texts.
+    Same(String),
+    /// The line is only present in the old text.
+    Removed(String),
+    /// The line is only present in the new text.
+    Added(String),
+}
+
+/// Compute a line-by-line diff from `old` to `new`.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| {
+            let line = change.value().trim_end_matches('\n').to_string();
+            match change.tag() {
+                ChangeTag::Equal => DiffLine::Same(line),
+                ChangeTag::Delete => DiffLine::Removed(line),
+                ChangeTag::Insert => DiffLine::Added(line),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn external_changes_are_detected_until_resolved() {
+        let name = format!("rhai-learning-document-{}.rhai", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, "1").unwrap();
+        let mut doc = Document::open(path.clone());
+        assert!(!doc.is_dirty());
+        assert_eq!(doc.disk_change(&path), None);
+
+        doc.text.push_str(" + 1");
+        assert!(doc.is_dirty());
+        std::fs::write(&path, "2").unwrap();
+        let Some(DiskChange::Changed(theirs)) = doc.disk_change(&path) else {
+            panic!("changed on disk");
+        };
+
+        doc.keep_mine(path.clone(), theirs);
+        assert!(doc.is_dirty());
+        assert_eq!(doc.disk_change(&path), None);
+        doc.save().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1 + 1");
+
+        // A deleted file keeps the text as unsaved changes.
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(doc.disk_change(&path), Some(DiskChange::Deleted));
+        doc.mark_deleted(path.clone());
+        assert!(doc.is_dirty());
+        assert_eq!(doc.text, "1 + 1");
+        assert_eq!(doc.disk_change(&path), None);
+        doc.save().unwrap();
+        assert!(!doc.is_dirty());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1 + 1");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn line_diff_marks_removed_and_added_lines() {
+        let diff = line_diff("a\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(
+            diff,
+            [
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Added("B".to_string()),
+                DiffLine::Same("c".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Code editor widget used for example scripts and module files.
+
+use super::complete::{self, Completion};
+use super::highlight::{self, Span, TokenKind};
+use eframe::egui;
+use egui::text::{CCursor, CCursorRange, LayoutJob, TextFormat};
+use egui::text_edit::{TextEditOutput, TextEditState};
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Background colour of a line that caused an error.
+const ERROR_LINE_BG: egui::Color32 = egui::Color32::from_rgb(90, 30, 30);
+
+/// Background colour of the line the debugger is paused at.
+const PAUSED_LINE_BG: egui::Color32 = egui::Color32::from_rgb(80, 70, 20);
+
+/// Colour of the breakpoint markers in the gutter.
+const BREAKPOINT: egui::Color32 = egui::Color32::from_rgb(220, 50, 50);
+
+/// Width of the gutter with line numbers and breakpoints, in points.
+const GUTTER_WIDTH: i8 = 44;
+
+/// Colour of the line under text with a diagnostic.
+const ERROR_UNDERLINE: egui::Color32 = egui::Color32::from_rgb(230, 60, 60);
+
+/// Convert a 1-based line and column into a character index into `text`.
+pub fn char_index(text: &str, line: usize, column: usize) -> usize {
+    let mut index = 0;
+    for (i, l) in text.split('\n').enumerate() {
+        if i + 1 == line {
+            return index + column.saturating_sub(1).min(l.chars().count());
+        }
+        index += l.chars().count() + 1;
+    }
+    text.chars().count()
+}
+
+/// A problem reported at a position of the edited text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Line of the problem, starting at 1.
+    pub line: usize,
+    /// Column of the problem, starting at 1.
+    pub column: usize,
+    /// Message shown when hovering the underlined text.
+    pub message: String,
+}
+
+/// Characters underlined for `diagnostic`: the word at its position, or the
+/// character before it at the end of a line.
+pub fn diagnostic_chars(text: &str, diagnostic: &Diagnostic) -> Range<usize> {
+    let start = char_index(text, diagnostic.line, diagnostic.column);
+    let len = text
+        .chars()
+        .skip(start)
+        .take_while(|c| !c.is_whitespace())
+        .count();
+    if len > 0 {
+        return start..start + len;
+    }
+    match text.chars().nth(start.wrapping_sub(1)) {
+        Some(c) if c != '\n' => start - 1..start,
+        _ => start..start,
+    }
+}
+
+/// Lines marked in the editor and its gutter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineMarks {
+    /// Line drawn with an error background, starting at 1.
+    pub error: Option<usize>,
+    /// Line the debugger is paused at, drawn with an arrow in the gutter.
+    pub paused: Option<usize>,
+    /// Lines with a breakpoint, toggled by clicking the gutter.
+    pub breakpoints: BTreeSet<usize>,
+}
+
+/// What the editor adds to the text of a Rhai script: highlighting, marked
+/// lines and breakpoints, diagnostics and completions.
+pub struct Script<'a> {
+    /// Lines marked in the editor and its gutter.
+    pub marks: &'a mut LineMarks,
+    /// Problems underlined in the text.
+    pub diagnostics: &'a [Diagnostic],
+    /// Completions offered for the word being typed.
+    pub completions: &'a [&'a Completion],
+}
+
+/// Byte offset of the character at `index` in `text`.
+fn byte_index(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(i, _)| i)
+}
+
+/// Replace the selection of the editor `id`, or insert at its cursor, with
+/// `insert` and focus the editor with the cursor after the inserted text.
+pub fn insert_at_cursor(ctx: &egui::Context, id: egui::Id, text: &mut String, insert: &str) {
+    let mut state = TextEditState::load(ctx, id).unwrap_or_default();
+    let end = text.chars().count();
+    let (start, stop) = state.cursor.char_range().map_or((end, end), |r| {
+        let [a, b] = r.sorted_cursors();
+        (a.index.min(end), b.index.min(end))
+    });
+    text.replace_range(byte_index(text, start)..byte_index(text, stop), insert);
+    let cursor = CCursor::new(start + insert.chars().count());
+    state.cursor.set_char_range(Some(CCursorRange::one(cursor)));
+    state.store(ctx, id);
+    ctx.memory_mut(|m| m.request_focus(id));
+}
+
+/// Show a multi-line code editor for `text`, as a Rhai script if `script` is
+/// given and as plain text otherwise.
+///
+/// For a script, the lines in `marks` are highlighted and clicking the gutter
+/// toggles a breakpoint in `marks`; `diagnostics` are underlined with their
+/// message shown on hover and `completions` are offered for the word being
+/// typed. `select` selects the given characters, or moves the cursor to an
+/// empty range, and scrolls to them.
+pub fn show(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    text: &mut String,
+    mut script: Option<Script<'_>>,
+    select: Option<Range<usize>>,
+) -> TextEditOutput {
+    let jump = select.map(|r| (CCursor::new(r.start), CCursor::new(r.end)));
+    if let Some((start, end)) = jump {
+        let mut state = TextEditState::load(ui.ctx(), id).unwrap_or_default();
+        state
+            .cursor
+            .set_char_range(Some(CCursorRange::two(start, end)));
+        state.store(ui.ctx(), id);
+        ui.memory_mut(|m| m.request_focus(id));
+    }
+
+    let mut layouter = |ui: &egui::Ui, buf: &dyn egui::TextBuffer, wrap_width: f32| {
+        let text = buf.as_str();
+        let mut job = match &script {
+            Some(script) => {
+                let underlines: Vec<Range<usize>> = script
+                    .diagnostics
+                    .iter()
+                    .map(|d| {
+                        let chars = diagnostic_chars(text, d);
+                        byte_index(text, chars.start)..byte_index(text, chars.end)
+                    })
+                    .collect();
+                let spans = highlight::highlight(ui.ctx(), text);
+                layout_lines(ui, text, &spans, script.marks, &underlines)
+            }
+            None => {
+                let spans = [(0..text.len(), TokenKind::Plain)];
+                layout_lines(ui, text, &spans, &LineMarks::default(), &[])
+            }
+        };
+        job.wrap.max_width = wrap_width;
+        ui.fonts(|f| f.layout_job(job))
+    };
+
+    let accept = script.is_some() && complete::handle_keys(ui, id);
+    let output = egui::TextEdit::multiline(text)
+        .id(id)
+        .code_editor()
+        .desired_rows(20)
+        .desired_width(f32::INFINITY)
+        .margin(egui::Margin {
+            left: GUTTER_WIDTH,
+            right: 4,
+            top: 2,
+            bottom: 2,
+        })
+        .layouter(&mut layouter)
+        .show(ui);
+    show_gutter(ui, id, &output, script.as_mut().map(|s| &mut *s.marks));
+
+    if let Some((cursor, _)) = jump {
+        let rect = output
+            .galley
+            .pos_from_cursor(cursor)
+            .translate(output.galley_pos.to_vec2());
+        ui.scroll_to_rect(rect, Some(egui::Align::Center));
+    }
+
+    let Some(script) = script else {
+        return output;
+    };
+    complete::show(ui, id, text, &output, script.completions, accept);
+
+    if let Some(pointer) = output.response.hover_pos() {
+        let offset = output.galley_pos.to_vec2();
+        let hovered = script.diagnostics.iter().find(|d| {
+            let chars = diagnostic_chars(text, d);
+            let start = output.galley.pos_from_cursor(CCursor::new(chars.start));
+            let end = output.galley.pos_from_cursor(CCursor::new(chars.end));
+            start.union(end).translate(offset).contains(pointer)
+        });
+        if let Some(diagnostic) = hovered {
+            output
+                .response
+                .clone()
+                .on_hover_text_at_pointer(&diagnostic.message);
+        }
+    }
+
+    output
+}
+
+/// Draw line numbers in the left margin of the editor and, given the `marks`
+/// of a script, its breakpoints and paused line, toggling the breakpoint of a
+/// clicked line.
+fn show_gutter(
+    ui: &egui::Ui,
+    id: egui::Id,
+    output: &TextEditOutput,
+    mut marks: Option<&mut LineMarks>,
+) {
+    let rect = output.response.rect;
+    let gutter =
+        egui::Rect::from_min_max(rect.min, egui::pos2(output.galley_pos.x - 2.0, rect.max.y));
+    let clicked_y = marks
+        .is_some()
+        .then(|| ui.interact(gutter, id.with("gutter"), egui::Sense::click()))
+        .filter(|response| response.clicked())
+        .and_then(|response| response.interact_pointer_pos())
+        .map(|p| p.y);
+
+    let painter = ui.painter_at(gutter);
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let weak = ui.visuals().weak_text_color();
+    let mut line = 1;
+    let mut first_row = true;
+    for row in &output.galley.rows {
+        let row_rect = row.rect().translate(output.galley_pos.to_vec2());
+        if first_row {
+            let mid = row_rect.center().y;
+            if let Some(marks) = marks.as_deref_mut() {
+                if clicked_y.is_some_and(|y| row_rect.min.y <= y && y < row_rect.max.y)
+                    && !marks.breakpoints.remove(&line)
+                {
+                    marks.breakpoints.insert(line);
+                }
+                if marks.breakpoints.contains(&line) {
+                    let radius = (row_rect.height() * 0.3).min(5.0);
+                    painter.circle_filled(egui::pos2(gutter.min.x + 8.0, mid), radius, BREAKPOINT);
+                }
+                if marks.paused == Some(line) {
+                    painter.text(
+                        egui::pos2(gutter.min.x + 8.0, mid),
+                        egui::Align2::CENTER_CENTER,
+                        "▶",
+                        font.clone(),
+                        egui::Color32::YELLOW,
+                    );
+                }
+            }
+            painter.text(
+                egui::pos2(gutter.max.x - 4.0, mid),
+                egui::Align2::RIGHT_CENTER,
+                line.to_string(),
+                font.clone(),
+                weak,
+            );
+        }
+        first_row = row.row.ends_with_newline;
+        if first_row {
+            line += 1;
+        }
+    }
+}
+
+/// Lay out `text` in the monospace font coloured by `spans`, highlighting the
+/// error and paused lines of `marks` and underlining the byte ranges in
+/// `underlines`.
+fn layout_lines(
+    ui: &egui::Ui,
+    text: &str,
+    spans: &[Span],
+    marks: &LineMarks,
+    underlines: &[Range<usize>],
+) -> LayoutJob {
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let dark_mode = ui.visuals().dark_mode;
+
+    let mut job = LayoutJob::default();
+    let mut line = 1;
+    for (range, kind) in spans {
+        let mut format = TextFormat::simple(font.clone(), kind.color(dark_mode));
+        let mut start = range.start;
+        while start < range.end {
+            // Split at line ends and where an underline starts or stops.
+            let line_end = text[start..range.end]
+                .find('\n')
+                .map_or(range.end, |i| start + i + 1);
+            let end = underlines
+                .iter()
+                .flat_map(|u| [u.start, u.end])
+                .filter(|b| (start + 1..line_end).contains(b))
+                .min()
+                .unwrap_or(line_end);
+            format.background = if marks.error == Some(line) {
+                ERROR_LINE_BG
+            } else if marks.paused == Some(line) {
+                PAUSED_LINE_BG
+            } else {
+                egui::Color32::TRANSPARENT
+            };
+            format.underline = if underlines.iter().any(|u| u.contains(&start)) {
+                egui::Stroke::new(1.5, ERROR_UNDERLINE)
+            } else {
+                egui::Stroke::NONE
+            };
+            job.append(&text[start..end], 0.0, format.clone());
+            if text[..end].ends_with('\n') {
+                line += 1;
+            }
+            start = end;
+        }
+    }
+    job
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_index_counts_lines_and_columns_from_one() {
+        let text = "let x = 1;\nlet y = ;\n";
+        assert_eq!(char_index(text, 1, 1), 0);
+        assert_eq!(char_index(text, 2, 9), 19);
+        assert_eq!(char_index(text, 2, 99), 20);
+        assert_eq!(char_index(text, 9, 1), text.chars().count());
+    }
+
+    #[test]
+    fn diagnostics_underline_the_word_at_their_position() {
+        let text = "let y = foo bar;\nlet z =\n";
+        let at = |line, column| Diagnostic {
+            line,
+            column,
+            message: String::new(),
+        };
+        assert_eq!(diagnostic_chars(text, &at(1, 9)), 8..11);
+        assert_eq!(diagnostic_chars(text, &at(2, 8)), 23..24);
+        assert_eq!(diagnostic_chars(text, &at(3, 1)), 25..25);
+    }
+}
@@ -0,0 +1,339 @@
+//! Rhai syntax highlighting for the code editor.
+
+use Rhai_Learning::host::host_function_names;
+use eframe::egui;
+use egui::util::cache::{ComputerMut, FrameCache};
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+/// Kinds of source text that are coloured differently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    /// The `${` and `}` around an interpolation in a template string.
+    Interpolation,
+    Number,
+    Comment,
+    Operator,
+    /// Name of a registered host function that is being called.
+    HostFunction,
+}
+
+impl TokenKind {
+    /// Text colour of this kind of token for a dark or light theme.
+    pub fn color(self, dark_mode: bool) -> egui::Color32 {
+        use egui::Color32 as C;
+        match (self, dark_mode) {
+            (TokenKind::Plain, true) => C::from_rgb(220, 220, 220),
+            (TokenKind::Plain, false) => C::from_rgb(30, 30, 30),
+            (TokenKind::Keyword, true) => C::from_rgb(197, 134, 192),
+            (TokenKind::Keyword, false) => C::from_rgb(140, 30, 150),
+            (TokenKind::String, true) => C::from_rgb(206, 145, 120),
+            (TokenKind::String, false) => C::from_rgb(163, 21, 21),
+            (TokenKind::Interpolation, true) => C::from_rgb(86, 156, 214),
+            (TokenKind::Interpolation, false) => C::from_rgb(0, 80, 200),
+            (TokenKind::Number, true) => C::from_rgb(181, 206, 168),
+            (TokenKind::Number, false) => C::from_rgb(9, 134, 88),
+            (TokenKind::Comment, true) => C::from_rgb(106, 153, 85),
+            (TokenKind::Comment, false) => C::from_rgb(0, 128, 0),
+            (TokenKind::Operator, true) => C::from_rgb(212, 212, 170),
+            (TokenKind::Operator, false) => C::from_rgb(100, 100, 60),
+            (TokenKind::HostFunction, true) => C::from_rgb(78, 201, 176),
+            (TokenKind::HostFunction, false) => C::from_rgb(38, 127, 153),
+        }
+    }
+}
+
+/// A highlighted byte range of the source.
+pub type Span = (Range<usize>, TokenKind);
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "catch", "const", "continue", "do", "else", "export", "false", "fn", "for",
+    "global", "if", "import", "in", "let", "loop", "private", "return", "switch", "this", "throw",
+    "true", "try", "until", "while",
+];
+
+/// Characters highlighted as operators.
+const OPERATORS: &[u8] = b"+-*/%=!<>&|^?:.~@";
+
+/// Names of the host functions, computed once.
+fn host_functions() -> &'static [String] {
+    static NAMES: OnceLock<Vec<String>> = OnceLock::new();
+    NAMES.get_or_init(host_function_names)
+}
+
+#[derive(Default)]
+struct Highlighter;
+
+impl ComputerMut<&str, Arc<Vec<Span>>> for Highlighter {
+    fn compute(&mut self, text: &str) -> Arc<Vec<Span>> {
+        Arc::new(tokenize(text, host_functions()))
+    }
+}
+
+type HighlightCache = FrameCache<Arc<Vec<Span>>, Highlighter>;
+
+/// Highlight `text`, reusing the result of the previous frame if it is unchanged.
+pub fn highlight(ctx: &egui::Context, text: &str) -> Arc<Vec<Span>> {
+    ctx.memory_mut(|m| m.caches.cache::<HighlightCache>().get(text))
+}
+
+/// Split `text` into spans covering all of it, calling out the names in
+/// `host_functions` where they are called.
+pub fn tokenize(text: &str, host_functions: &[String]) -> Vec<Span> {
+    let mut lexer = Lexer {
+        text,
+        bytes: text.as_bytes(),
+        pos: 0,
+        spans: Vec::new(),
+        host_functions,
+    };
+    lexer.code(false);
+    lexer.spans
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    spans: Vec<Span>,
+    host_functions: &'a [String],
+}
+
+impl Lexer<'_> {
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    /// Add a span ending at the current position, merging it with the previous
+    /// span of the same kind.
+    fn push(&mut self, start: usize, kind: TokenKind) {
+        if start == self.pos {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some((range, last)) if *last == kind && range.end == start => range.end = self.pos,
+            _ => self.spans.push((start..self.pos, kind)),
+        }
+    }
+
+    fn skip_while(&mut self, f: impl Fn(u8) -> bool) {
+        while self.peek(0).is_some_and(&f) {
+            self.pos += 1;
+        }
+    }
+
+    /// Lex code up to the end, or up to the `}` closing an interpolation.
+    fn code(&mut self, interpolation: bool) {
+        let mut depth = 0;
+        while let Some(c) = self.peek(0) {
+            let start = self.pos;
+            match c {
+                b'/' if self.peek(1) == Some(b'/') => {
+                    self.skip_while(|c| c != b'\n');
+                    self.push(start, TokenKind::Comment);
+                }
+                b'/' if self.peek(1) == Some(b'*') => self.block_comment(),
+                b'"' | b'\'' => self.quoted(c),
+                b'`' => self.template(),
+                b'0'..=b'9' => self.number(),
+                b'a'..=b'z' | b'A'..=b'Z' | b'_' => self.word(),
+                b'{' => {
+                    depth += 1;
+                    self.pos += 1;
+                    self.push(start, TokenKind::Plain);
+                }
+                b'}' if interpolation && depth == 0 => return,
+                b'}' => {
+                    depth -= 1;
+                    self.pos += 1;
+                    self.push(start, TokenKind::Plain);
+                }
+                c if OPERATORS.contains(&c) => {
+                    self.pos += 1;
+                    self.push(start, TokenKind::Operator);
+                }
+                _ => {
+                    let len = self.text[start..].chars().next().map_or(1, char::len_utf8);
+                    self.pos += len;
+                    self.push(start, TokenKind::Plain);
+                }
+            }
+        }
+    }
+
+    /// A `/* */` comment, which may be nested.
+    fn block_comment(&mut self) {
+        let start = self.pos;
+        let mut depth = 0;
+        while self.pos < self.bytes.len() {
+            if self.peek(0) == Some(b'/') && self.peek(1) == Some(b'*') {
+                depth += 1;
+                self.pos += 2;
+            } else if self.peek(0) == Some(b'*') && self.peek(1) == Some(b'/') {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    break;
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+        self.push(start, TokenKind::Comment);
+    }
+
+    /// A string or character literal delimited by `quote`, ending at the line
+    /// end if it is not closed.
+    fn quoted(&mut self, quote: u8) {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(c) = self.peek(0) {
+            match c {
+                b'\\' => self.pos = (self.pos + 2).min(self.bytes.len()),
+                b'\n' => break,
+                _ => {
+                    self.pos += 1;
+                    if c == quote {
+                        break;
+                    }
+                }
+            }
+        }
+        self.push(start, TokenKind::String);
+    }
+
+    /// A backtick template string, lexing `${ ... }` interpolations as code.
+    fn template(&mut self) {
+        let mut start = self.pos;
+        self.pos += 1;
+        while let Some(c) = self.peek(0) {
+            match c {
+                b'\\' => self.pos = (self.pos + 2).min(self.bytes.len()),
+                b'`' => {
+                    self.pos += 1;
+                    break;
+                }
+                b'$' if self.peek(1) == Some(b'{') => {
+                    self.push(start, TokenKind::String);
+                    let open = self.pos;
+                    self.pos += 2;
+                    self.push(open, TokenKind::Interpolation);
+                    self.code(true);
+                    let close = self.pos;
+                    if self.peek(0) == Some(b'}') {
+                        self.pos += 1;
+                    }
+                    self.push(close, TokenKind::Interpolation);
+                    start = self.pos;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        self.push(start, TokenKind::String);
+    }
+
+    fn number(&mut self) {
+        let start = self.pos;
+        if self.peek(0) == Some(b'0') && matches!(self.peek(1), Some(b'x' | b'o' | b'b')) {
+            self.pos += 2;
+            self.skip_while(|c| c.is_ascii_hexdigit() || c == b'_');
+        } else {
+            self.skip_while(|c| c.is_ascii_digit() || c == b'_');
+            if self.peek(0) == Some(b'.') && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+                self.skip_while(|c| c.is_ascii_digit() || c == b'_');
+            }
+            if matches!(self.peek(0), Some(b'e' | b'E')) {
+                let sign = usize::from(matches!(self.peek(1), Some(b'+' | b'-')));
+                if self.peek(1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1 + sign;
+                    self.skip_while(|c| c.is_ascii_digit());
+                }
+            }
+        }
+        self.push(start, TokenKind::Number);
+    }
+
+    fn word(&mut self) {
+        let start = self.pos;
+        self.skip_while(|c| c.is_ascii_alphanumeric() || c == b'_');
+        let word = &self.text[start..self.pos];
+        let kind = if KEYWORDS.contains(&word) {
+            TokenKind::Keyword
+        } else if self.host_functions.iter().any(|f| f == word) && self.is_call() {
+            TokenKind::HostFunction
+        } else {
+            TokenKind::Plain
+        };
+        self.push(start, kind);
+    }
+
+    /// Whether the next non-blank character opens an argument list.
+    fn is_call(&self) -> bool {
+        self.bytes[self.pos..]
+            .iter()
+            .find(|c| !c.is_ascii_whitespace())
+            .is_some_and(|c| *c == b'(')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<(&str, TokenKind)> {
+        let hosts = ["http_get".to_string(), "Point".to_string()];
+        tokenize(text, &hosts)
+            .into_iter()
+            .filter(|(_, kind)| *kind != TokenKind::Plain)
+            .map(|(range, kind)| (&text[range], kind))
+            .collect()
+    }
+
+    #[test]
+    fn spans_cover_the_whole_text() {
+        let text = "let é = `a ${x + 1} b`; // done\n/* a /* nested */ comment */ 0x1F";
+        let spans = tokenize(text, &[]);
+        let mut end = 0;
+        for (range, _) in &spans {
+            assert_eq!(range.start, end);
+            end = range.end;
+        }
+        assert_eq!(end, text.len());
+    }
+
+    #[test]
+    fn tokens_are_classified() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("let p = Point(1, 2.5e3); // c"),
+            [
+                ("let", Keyword),
+                ("=", Operator),
+                ("Point", HostFunction),
+                ("1", Number),
+                ("2.5e3", Number),
+                ("// c", Comment),
+            ]
+        );
+        assert_eq!(
+            kinds("`x${http_get(\"u\")}y` + Point"),
+            [
+                ("`x", String),
+                ("${", Interpolation),
+                ("http_get", HostFunction),
+                ("\"u\"", String),
+                ("}", Interpolation),
+                ("y`", String),
+                ("+", Operator),
+            ]
+        );
+        assert_eq!(
+            kinds("0..5"),
+            [("0", Number), ("..", Operator), ("5", Number)]
+        );
+    }
+}
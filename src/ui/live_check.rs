@@ -0,0 +1,149 @@
+//! Compilation of the edited script in the background while typing.
+
+use super::complete::{Completion, script_completions};
+use super::editor::Diagnostic;
+use crate::examples::{AstExport, AstNode, Example, syntax_tree};
+use std::sync::mpsc::{Receiver, channel};
+use std::time::{Duration, Instant};
+
+/// How long the text must stay unchanged before it is compiled.
+const IDLE: Duration = Duration::from_millis(500);
+
+/// How often to look for the result of a running check.
+const POLL: Duration = Duration::from_millis(50);
+
+/// Problems found by a check, and the functions defined, syntax tree and its
+/// JSON export if the text compiled.
+type CheckResult = (
+    Vec<Diagnostic>,
+    Option<(Vec<Completion>, Vec<AstNode>, String)>,
+);
+
+/// Compiles the edited text once editing pauses, without running it.
+pub struct LiveCheck {
+    /// Last edited text and when it was first seen, waiting for the idle period.
+    edited: Option<(String, Instant)>,
+    /// Text of the last check that was started.
+    checked: String,
+    /// Problems found in `checked`, or `None` while the check is running.
+    diagnostics: Option<Vec<Diagnostic>>,
+    /// Functions defined by the last text that compiled.
+    functions: Vec<Completion>,
+    /// Syntax tree of the last text that compiled.
+    tree: Vec<AstNode>,
+    /// [`AstExport`] JSON of the same text as `tree`.
+    ast_json: String,
+    /// Receives the result of the running check.
+    rx: Option<Receiver<CheckResult>>,
+}
+
+impl Default for LiveCheck {
+    fn default() -> Self {
+        // The empty text compiles.
+        Self {
+            edited: None,
+            checked: String::new(),
+            diagnostics: Some(Vec::new()),
+            functions: Vec::new(),
+            tree: Vec::new(),
+            ast_json: String::new(),
+            rx: None,
+        }
+    }
+}
+
+impl LiveCheck {
+    /// Note the current `text`, compiling it on a background thread with the
+    /// engine configuration of `example` once it has not changed for a while.
+    ///
+    /// Returns when to call again, if a check is waiting or running.
+    pub fn update(&mut self, example: &Example, text: &str) -> Option<Duration> {
+        if let Some(rx) = &self.rx
+            && let Ok((diagnostics, compiled)) = rx.try_recv()
+        {
+            self.diagnostics = Some(diagnostics);
+            if let Some((functions, tree, ast_json)) = compiled {
+                self.functions = functions;
+                self.tree = tree;
+                self.ast_json = ast_json;
+            }
+            self.rx = None;
+        }
+        if text == self.checked {
+            self.edited = None;
+            return self.rx.as_ref().map(|_| POLL);
+        }
+        match &self.edited {
+            Some((edited, since)) if edited == text => {
+                if let Some(wait) = IDLE.checked_sub(since.elapsed()) {
+                    return Some(wait);
+                }
+            }
+            _ => {
+                self.edited = Some((text.to_string(), Instant::now()));
+                return Some(IDLE);
+            }
+        }
+
+        self.edited = None;
+        self.checked = text.to_string();
+        self.diagnostics = None;
+        let (tx, rx) = channel();
+        let example = example.clone();
+        let script = self.checked.clone();
+        std::thread::spawn(move || {
+            let result = match example.check_script(&script) {
+                Ok(ast) => (
+                    Vec::new(),
+                    Some((
+                        script_completions(&ast),
+                        syntax_tree(&ast),
+                        AstExport::new(&ast).to_json(),
+                    )),
+                ),
+                Err(err) => {
+                    let diagnostic = Diagnostic {
+                        line: err.line.unwrap_or(1),
+                        column: err.column.unwrap_or(1),
+                        message: format!("{}: {}", err.kind, err.message),
+                    };
+                    (vec![diagnostic], None)
+                }
+            };
+            let _ = tx.send(result);
+        });
+        self.rx = Some(rx);
+        Some(POLL)
+    }
+
+    /// Problems found in `text`, or `None` if it has not been checked yet.
+    pub fn diagnostics(&self, text: &str) -> Option<&[Diagnostic]> {
+        self.diagnostics.as_deref().filter(|_| text == self.checked)
+    }
+
+    /// Functions defined by the last checked text that compiled.
+    pub fn functions(&self) -> &[Completion] {
+        &self.functions
+    }
+
+    /// Syntax tree of the last checked text that compiled.
+    pub fn tree(&self) -> &[AstNode] {
+        &self.tree
+    }
+
+    /// JSON export of the syntax tree returned by [`tree`](Self::tree), empty
+    /// if no text has compiled yet.
+    pub fn ast_json(&self) -> &str {
+        &self.ast_json
+    }
+
+    /// Status line describing the check of `text`.
+    pub fn status(&self, text: &str) -> String {
+        match self.diagnostics(text).map(<[Diagnostic]>::len) {
+            None => "Checking…".to_string(),
+            Some(0) => "compiles OK".to_string(),
+            Some(1) => "1 error".to_string(),
+            Some(n) => format!("{n} errors"),
+        }
+    }
+}
@@ -1,3 +1,13 @@
 //! Graphical user interface components built with `egui`.
 
+pub mod api_browser;
 pub mod app;
+pub mod ast_view;
+pub mod complete;
+pub mod document;
+pub mod editor;
+pub mod highlight;
+pub mod live_check;
+pub mod optimize_view;
+pub mod scope_view;
+pub mod tabs;
@@ -0,0 +1,114 @@
+//! Side-by-side view of a script compiled under each optimization level.
+
+use super::document::{DiffLine, line_diff};
+use crate::examples::LevelRun;
+use eframe::egui;
+
+/// Background of AST lines the optimizer removed.
+const REMOVED_BG: egui::Color32 = egui::Color32::from_rgb(90, 30, 30);
+
+/// Background of AST lines the optimizer produced.
+const ADDED_BG: egui::Color32 = egui::Color32::from_rgb(30, 80, 40);
+
+/// Lines of an AST outline with the background to draw them with, from the
+/// diff of the unoptimized `baseline` to an `optimized` outline.
+///
+/// With `show_baseline` the baseline lines are returned, marking those the
+/// optimizer removed; otherwise the optimized lines, marking those it produced.
+fn marked_lines(
+    baseline: &str,
+    optimized: &str,
+    show_baseline: bool,
+) -> Vec<(String, Option<egui::Color32>)> {
+    line_diff(baseline, optimized)
+        .into_iter()
+        .filter_map(|line| match line {
+            DiffLine::Same(l) => Some((l, None)),
+            DiffLine::Removed(l) if show_baseline => Some((l, Some(REMOVED_BG))),
+            DiffLine::Added(l) if !show_baseline => Some((l, Some(ADDED_BG))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Show one column per run with its result, timings and AST outline.
+///
+/// The first run is the unoptimized baseline and marks what the last run
+/// removed; the other runs mark what differs from the baseline.
+pub fn show(ui: &mut egui::Ui, runs: &[LevelRun]) {
+    let (Some(baseline), Some(last)) = (runs.first(), runs.last()) else {
+        return;
+    };
+    ui.columns(runs.len(), |columns| {
+        for (i, (ui, run)) in columns.iter_mut().zip(runs).enumerate() {
+            ui.heading(format!("{:?}", run.level));
+            match &run.result.error {
+                Some(err) => {
+                    ui.colored_label(
+                        egui::Color32::LIGHT_RED,
+                        format!("{}: {}", err.kind, err.message),
+                    );
+                }
+                None => {
+                    ui.monospace(format!("=> {:?}", run.result.value));
+                }
+            }
+            ui.label(format!(
+                "compile {:.2} ms, run {:.2} ms",
+                run.compile_time.as_secs_f64() * 1000.0,
+                run.run_time.as_secs_f64() * 1000.0
+            ));
+            ui.separator();
+            egui::ScrollArea::both()
+                .id_salt(("optimization", format!("{:?}", run.level)))
+                .show(ui, |ui| {
+                    let lines = if i == 0 {
+                        marked_lines(&baseline.outline, &last.outline, true)
+                    } else {
+                        marked_lines(&baseline.outline, &run.outline, false)
+                    };
+                    for (line, bg) in lines {
+                        let mut text = egui::RichText::new(line).monospace();
+                        if let Some(bg) = bg {
+                            text = text.background_color(bg);
+                        }
+                        ui.label(text);
+                    }
+                });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baseline_marks_removed_lines_and_levels_mark_added_ones() {
+        let none = "Let y\n  Operator +\n    Integer 3\n    Integer 4\n";
+        let full = "Let y\n  Integer 7\n";
+        let marked = |show_baseline| {
+            marked_lines(none, full, show_baseline)
+                .into_iter()
+                .map(|(l, bg)| (l.trim().to_string(), bg))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            marked(true),
+            [
+                ("Let y".to_string(), None),
+                ("Operator +".to_string(), Some(REMOVED_BG)),
+                ("Integer 3".to_string(), Some(REMOVED_BG)),
+                ("Integer 4".to_string(), Some(REMOVED_BG)),
+            ]
+        );
+        assert_eq!(
+            marked(false),
+            [
+                ("Let y".to_string(), None),
+                ("Integer 7".to_string(), Some(ADDED_BG))
+            ]
+        );
+    }
+}
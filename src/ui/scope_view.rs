@@ -0,0 +1,65 @@
+//! Expandable list of the variables in a script's scope.
+
+use crate::examples::Variable;
+use eframe::egui;
+use egui::collapsing_header::CollapsingState;
+
+/// One line describing `variable`: `name: type = value`, prefixed with
+/// `const` for constants.
+fn row_text(variable: &Variable) -> String {
+    let constant = if variable.constant { "const " } else { "" };
+    format!(
+        "{constant}{}: {} = {}",
+        variable.name, variable.type_name, variable.value
+    )
+}
+
+/// Show `variables` one per line, with arrays and maps expandable.
+///
+/// Expansion is remembered by variable name under `id`, so it is kept while
+/// the values change between runs and steps.
+pub fn show(ui: &mut egui::Ui, id: egui::Id, variables: &[Variable]) {
+    if variables.is_empty() {
+        ui.weak("No variables in scope");
+        return;
+    }
+    show_variables(ui, id, variables);
+}
+
+fn show_variables(ui: &mut egui::Ui, id: egui::Id, variables: &[Variable]) {
+    for variable in variables {
+        let text = egui::RichText::new(row_text(variable)).monospace();
+        if variable.children.is_empty() {
+            ui.horizontal(|ui| {
+                ui.add_space(ui.spacing().indent);
+                ui.label(text);
+            });
+        } else {
+            let id = id.with(&variable.name);
+            CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                .show_header(ui, |ui| ui.label(text))
+                .body(|ui| show_variables(ui, id, &variable.children));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rhai::Dynamic;
+
+    #[test]
+    fn rows_show_name_type_and_value() {
+        let array: Dynamic = vec![Dynamic::from(1_i64), Dynamic::from("a")].into();
+        let numbers = Variable::new("numbers", &array);
+        assert_eq!(row_text(&numbers), "numbers: array = 2 items");
+        let items: Vec<String> = numbers.children.iter().map(row_text).collect();
+        assert_eq!(items, ["[0]: i64 = 1", "[1]: string = \"a\""]);
+
+        let limit = Variable {
+            constant: true,
+            ..Variable::new("LIMIT", &Dynamic::from(3.5_f64))
+        };
+        assert_eq!(row_text(&limit), "const LIMIT: f64 = 3.5");
+    }
+}
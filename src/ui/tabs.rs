@@ -0,0 +1,151 @@
+//! Tabs of the files open in the editor.
+
+use super::document::{DiskChange, Document};
+use std::path::{Path, PathBuf};
+
+/// A file open in an editor tab.
+pub struct Tab {
+    /// The file's text and unsaved changes.
+    pub doc: Document,
+    /// Change found on disk while the tab had unsaved changes, or deletion of
+    /// the file, with the path it was found at.
+    pub conflict: Option<(PathBuf, DiskChange)>,
+}
+
+impl Tab {
+    fn new(doc: Document) -> Self {
+        Self {
+            doc,
+            conflict: None,
+        }
+    }
+
+    /// Short name shown on the tab.
+    pub fn title(&self) -> String {
+        self.doc
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Whether the tab holds a Rhai script, which is highlighted, checked
+    /// while it is edited and can have breakpoints.
+    pub fn is_script(&self) -> bool {
+        self.doc.path.extension().is_some_and(|ext| ext == "rhai")
+    }
+
+    /// Pick up a change of the file at `path` made outside the editor.
+    ///
+    /// An unedited tab is reloaded; otherwise the version on disk is kept as
+    /// a conflict for the user to resolve. If the file was deleted, the text
+    /// is kept as unsaved changes and the deletion reported as a conflict.
+    pub fn sync(&mut self, path: PathBuf) {
+        match self.doc.disk_change(&path) {
+            None => {}
+            Some(DiskChange::Deleted) => {
+                self.doc.mark_deleted(path.clone());
+                self.conflict = Some((path, DiskChange::Deleted));
+            }
+            Some(change) if self.doc.is_dirty() => self.conflict = Some((path, change)),
+            Some(DiskChange::Changed(theirs)) => {
+                self.doc.take_theirs(path, theirs);
+                self.conflict = None;
+            }
+        }
+    }
+}
+
+/// Editor tabs. The first tab always holds the selected example's script.
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Self {
+            tabs: vec![Tab::new(Document::default())],
+            active: Self::SCRIPT,
+        }
+    }
+}
+
+impl Tabs {
+    /// Index of the tab holding the example script.
+    pub const SCRIPT: usize = 0;
+
+    /// Close every tab and show `script` in the script tab.
+    pub fn reset(&mut self, script: Document) {
+        self.tabs = vec![Tab::new(script)];
+        self.active = Self::SCRIPT;
+    }
+
+    /// The example script.
+    pub fn script(&self) -> &Document {
+        &self.tabs[Self::SCRIPT].doc
+    }
+
+    /// The example script, for editing.
+    pub fn script_mut(&mut self) -> &mut Document {
+        &mut self.tabs[Self::SCRIPT].doc
+    }
+
+    /// Index of the tab shown in the editor.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Show the tab at `index` in the editor.
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.active = index;
+        }
+    }
+
+    /// The tab at `index`.
+    pub fn get(&self, index: usize) -> Option<&Tab> {
+        self.tabs.get(index)
+    }
+
+    /// The tab at `index`, for editing.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Tab> {
+        self.tabs.get_mut(index)
+    }
+
+    /// Iterate over the tabs in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Tab> {
+        self.tabs.iter()
+    }
+
+    /// Iterate over the tabs in order, for editing.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Tab> {
+        self.tabs.iter_mut()
+    }
+
+    /// Index of the tab showing `path`, if any.
+    pub fn find(&self, path: &Path) -> Option<usize> {
+        self.tabs.iter().position(|t| t.doc.path == path)
+    }
+
+    /// Show `path` in a tab, opening one if needed, and return its index.
+    pub fn open(&mut self, path: PathBuf) -> usize {
+        let index = self.find(&path).unwrap_or_else(|| {
+            self.tabs.push(Tab::new(Document::open(path)));
+            self.tabs.len() - 1
+        });
+        self.active = index;
+        index
+    }
+
+    /// Close the tab at `index`. The script tab cannot be closed.
+    pub fn close(&mut self, index: usize) {
+        if index == Self::SCRIPT || index >= self.tabs.len() {
+            return;
+        }
+        self.tabs.remove(index);
+        if self.active >= index {
+            self.active -= 1;
+        }
+    }
+}
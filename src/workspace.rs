@@ -0,0 +1,153 @@
+//! Location of the examples, logs and other files the application works on.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the workspace root.
+pub const ROOT_ENV: &str = "RHAI_LEARNING_ROOT";
+
+/// Environment variable overriding the user workspace.
+pub const USER_ROOT_ENV: &str = "RHAI_LEARNING_USER_ROOT";
+
+/// Name of the optional configuration file in the user's config directory.
+const CONFIG_FILE: &str = "rhai-learning/config.toml";
+
+#[derive(Default, Deserialize)]
+struct Config {
+    root: Option<PathBuf>,
+    user_root: Option<PathBuf>,
+}
+
+/// Directories that every example, module and log path resolves against.
+///
+/// The root holds the bundled examples, which are never written to. Edits,
+/// the user's own examples and logs go to the user workspace, if there is one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Workspace {
+    root: PathBuf,
+    user_root: Option<PathBuf>,
+}
+
+impl Workspace {
+    /// Use `root` as the workspace root, without a user workspace.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            user_root: None,
+        }
+    }
+
+    /// Keep edits, user examples and logs in `user_root`.
+    pub fn with_user_root(mut self, user_root: impl Into<PathBuf>) -> Self {
+        self.user_root = Some(user_root.into());
+        self
+    }
+
+    /// Find the workspace root and the user workspace.
+    ///
+    /// The first of these wins: the `flag` given on the command line, the
+    /// [`ROOT_ENV`] environment variable, the `root` key of
+    /// `rhai-learning/config.toml` in the user's config directory (relative
+    /// to that file), the current directory if it contains
+    /// `examples/manifest.toml`, and finally the directory this crate was
+    /// built from.
+    ///
+    /// The user workspace is taken from [`USER_ROOT_ENV`], the `user_root`
+    /// key of the config file, or `rhai-learning` in the user's data directory.
+    pub fn detect(flag: Option<PathBuf>) -> Self {
+        let config = read_config();
+        let root = flag
+            .or_else(|| env_path(ROOT_ENV))
+            .or(config.root)
+            .unwrap_or_else(|| {
+                let cwd = std::env::current_dir().unwrap_or_default();
+                if cwd.join("examples/manifest.toml").is_file() {
+                    cwd
+                } else {
+                    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                }
+            });
+        let user_root = env_path(USER_ROOT_ENV)
+            .or(config.user_root)
+            .or_else(|| data_dir().map(|d| d.join("rhai-learning")));
+        Self { root, user_root }
+    }
+
+    /// The workspace root directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The writable user workspace, if there is one.
+    pub fn user_root(&self) -> Option<&Path> {
+        self.user_root.as_deref()
+    }
+
+    /// Directory holding the bundled example scripts and their manifest.
+    pub fn examples_dir(&self) -> PathBuf {
+        self.root.join("examples")
+    }
+
+    /// Path of the bundled example manifest.
+    pub fn manifest_path(&self) -> PathBuf {
+        self.examples_dir().join("manifest.toml")
+    }
+
+    /// Directory holding the user's examples and edited copies of bundled ones.
+    pub fn user_examples_dir(&self) -> Option<PathBuf> {
+        self.user_root.as_ref().map(|r| r.join("examples"))
+    }
+
+    /// Path of the optional manifest listing the user's own examples.
+    pub fn user_manifest_path(&self) -> Option<PathBuf> {
+        self.user_examples_dir().map(|d| d.join("manifest.toml"))
+    }
+
+    /// Where an edited copy of the bundled file `path` is kept.
+    ///
+    /// Returns `None` if `path` is not under [`examples_dir`](Self::examples_dir)
+    /// or there is no user workspace.
+    pub fn user_copy_path(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(self.examples_dir()).ok()?;
+        Some(self.user_examples_dir()?.join(relative))
+    }
+
+    /// Directory that example output and exported ASTs are written to.
+    pub fn logs_dir(&self) -> PathBuf {
+        self.user_root.as_ref().unwrap_or(&self.root).join("logs")
+    }
+}
+
+fn env_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+fn home_subdir(path: &str) -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(path))
+}
+
+fn data_dir() -> Option<PathBuf> {
+    env_path("XDG_DATA_HOME").or_else(|| home_subdir(".local/share"))
+}
+
+/// Read the user's configuration file, resolving its paths relative to the file.
+fn read_config() -> Config {
+    let Some(path) = env_path("XDG_CONFIG_HOME")
+        .or_else(|| home_subdir(".config"))
+        .map(|d| d.join(CONFIG_FILE))
+    else {
+        return Config::default();
+    };
+    let Some(mut config) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| toml::from_str::<Config>(&text).ok())
+    else {
+        return Config::default();
+    };
+    let base = path.parent().unwrap_or(Path::new(""));
+    config.root = config.root.map(|r| base.join(r));
+    config.user_root = config.user_root.map(|r| base.join(r));
+    config
+}
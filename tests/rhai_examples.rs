@@ -1,98 +1,951 @@
-use Rhai_Learning::examples::ExampleRegistry;
+use Rhai_Learning::examples::{
+    AST_JSON_VERSION, AstNode, Breakpoint, DebugCommand, ErrorKind, Example, ExampleRegistry,
+    Expectations, NodeKind, OPTIMIZATION_LEVELS, Origin, OutputExpectation, TestOutcome,
+    syntax_tree,
+};
+use Rhai_Learning::host::{
+    Capability, FnOrigin, HostEngine, LimitKind, Limits, host_function_names, is_cancelled,
+};
+use Rhai_Learning::workspace::Workspace;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// The bundled examples, failing the test if the manifest cannot be loaded.
+fn bundled_examples() -> Vec<Example> {
+    let examples = ExampleRegistry::all().expect("bundled manifest loads");
+    assert!(!examples.is_empty(), "no bundled examples");
+    examples
+}
+
+/// A workspace in its own temp directory, deleted when dropped.
+struct TempWorkspace(Workspace);
+
+impl std::ops::Deref for TempWorkspace {
+    type Target = Workspace;
+
+    fn deref(&self) -> &Workspace {
+        &self.0
+    }
+}
+
+impl Drop for TempWorkspace {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(self.0.root());
+    }
+}
+
+/// Create an empty workspace holding `files` in a temp directory unique to
+/// this call, so tests running in parallel or in several processes do not
+/// share it.
+fn temp_workspace(name: &str, files: &[(&str, &str)]) -> TempWorkspace {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let unique = format!(
+        "rhai-learning-{name}-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+    let root = std::env::temp_dir().join(unique);
+    let _ = std::fs::remove_dir_all(&root);
+    for (path, text) in files {
+        let path: PathBuf = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("create dir");
+        std::fs::write(path, text).expect("write file");
+    }
+    TempWorkspace(Workspace::new(root))
+}
 
 #[test]
 fn example_docs_exist() {
-    let registry = ExampleRegistry::all();
+    let registry = bundled_examples();
     for ex in &registry {
         assert!(ex.doc_html_path.exists(), "missing doc for {}", ex.id);
     }
 }
 
 #[test]
-fn hello_example_runs() {
-    let registry = ExampleRegistry::all();
+fn manifest_loads_without_problems() {
+    let workspace = Workspace::new(env!("CARGO_MANIFEST_DIR"));
+    let registry = ExampleRegistry::load(&workspace).expect("manifest loads");
+    assert!(registry.problems().is_empty(), "{:?}", registry.problems());
+    assert!(!registry.examples().is_empty());
+}
+
+#[test]
+fn manifest_problems_are_reported_with_lines() {
+    let manifest = r#"
+[[examples]]
+id = "hello"
+name = "Hello World"
+script = "hello.rhai"
+doc = "hello.html"
+
+[[examples]]
+id = "hello"
+name = "Hello again"
+script = "hello.rhai"
+doc = "hello.html"
+
+[[examples]]
+id = "missing"
+name = "Missing script"
+script = "does_not_exist.rhai"
+doc = "hello.html"
+
+[[examples]]
+id = "no-name"
+script = "hello.rhai"
+doc = "hello.html"
+
+[[examples]]
+id = "typo"
+name = "Misspelt expectation"
+script = "hello.rhai"
+doc = "hello.html"
+expected_stout = { exact = "1" }
+"#;
+    let workspace = temp_workspace(
+        "manifest-problems",
+        &[
+            ("examples/manifest.toml", manifest),
+            ("examples/hello.rhai", "1"),
+            ("examples/hello.md", "# Hello"),
+            ("examples/hello.html", "<h1>Hello</h1>"),
+        ],
+    );
+    let registry = ExampleRegistry::load(&workspace).expect("manifest parses");
+    let ids: Vec<&str> = registry.examples().iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["hello"]);
+
+    let problems: Vec<(Option<&str>, Option<usize>)> = registry
+        .problems()
+        .iter()
+        .map(|p| (p.example_id.as_deref(), p.line))
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            (Some("hello"), Some(8)),
+            (Some("missing"), Some(14)),
+            (None, Some(20)),
+            (None, Some(25)),
+        ]
+    );
+    let typo = &registry.problems()[3];
+    assert!(typo.message.contains("expected_stout"), "{typo}");
+
+    let manifest_path = workspace.manifest_path();
+    std::fs::write(&manifest_path, "[[examples]]\nid = \"oops\n").expect("write manifest");
+    let err = ExampleRegistry::load(&workspace)
+        .err()
+        .expect("invalid TOML");
+    assert_eq!(err.line, Some(2));
+}
+
+#[test]
+fn paths_resolve_against_the_workspace_root() {
+    let manifest = r#"
+[[examples]]
+id = "greet"
+name = "Greeting"
+script = "scripts/greet.rhai"
+doc = "docs/greet.html"
+capabilities = ["fs.read"]
+"#;
+    let workspace = temp_workspace(
+        "paths",
+        &[
+            ("examples/manifest.toml", manifest),
+            (
+                "examples/scripts/greet.rhai",
+                "import \"names\" as n; print(read_file(\"greeting.txt\") + n::NAME);",
+            ),
+            (
+                "examples/scripts/names.rhai",
+                "export const NAME = \"world\";",
+            ),
+            ("examples/scripts/greeting.txt", "hello "),
+            ("examples/docs/greet.md", "Greets."),
+            ("examples/docs/greet.html", "<p>Greets.</p>"),
+        ],
+    );
+    let registry = ExampleRegistry::load(&workspace).expect("manifest loads");
+    assert!(registry.problems().is_empty(), "{:?}", registry.problems());
+    let ex = &registry.examples()[0];
+    assert_eq!(ex.description, "Greets.");
+
+    let value = ex.run().expect("script runs");
+    assert!(value.is_unit());
+    let log = std::fs::read_to_string(workspace.logs_dir().join("greet.log")).expect("log");
+    assert_eq!(log, "hello world\n");
+}
+
+#[test]
+fn saving_a_bundled_example_creates_a_user_copy() {
+    let bundled = temp_workspace(
+        "bundled",
+        &[
+            (
+                "examples/manifest.toml",
+                "[[examples]]\nid = \"calc\"\nname = \"Calc\"\nscript = \"calc.rhai\"\ndoc = \"calc.html\"\n",
+            ),
+            (
+                "examples/calc.rhai",
+                "import \"lib\" as lib; lib::double(1)",
+            ),
+            ("examples/lib.rhai", "fn double(x) { x * 2 }"),
+            ("examples/calc.md", "Calc."),
+            ("examples/calc.html", "<p>Calc.</p>"),
+        ],
+    );
+    let user = temp_workspace(
+        "user",
+        &[(
+            "examples/manifest.toml",
+            "[[examples]]\nid = \"mine\"\nname = \"Mine\"\nscript = \"mine.rhai\"\ndoc = \"mine.html\"\n",
+        )],
+    );
+    std::fs::write(user.root().join("examples/mine.rhai"), "42").unwrap();
+    std::fs::write(user.root().join("examples/mine.md"), "Mine.").unwrap();
+    std::fs::write(user.root().join("examples/mine.html"), "<p>Mine.</p>").unwrap();
+    let workspace = Workspace::new(bundled.root()).with_user_root(user.root());
+
+    let registry = ExampleRegistry::load(&workspace).expect("manifests load");
+    assert!(registry.problems().is_empty(), "{:?}", registry.problems());
+    let ids: Vec<&str> = registry.examples().iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["calc", "mine"]);
+    assert_eq!(registry.examples()[1].origin, Origin::User);
+
+    let mut calc = registry.examples()[0].clone();
+    assert_eq!(calc.origin, Origin::Bundled);
+    assert!(!calc.is_modified());
+    calc.save_script("import \"lib\" as lib; lib::double(20)")
+        .expect("save copy");
+    assert!(calc.is_modified());
+    let original = bundled.root().join("examples/calc.rhai");
+    assert_eq!(
+        std::fs::read_to_string(&original).unwrap(),
+        "import \"lib\" as lib; lib::double(1)"
+    );
+
+    // The copy shadows the original on the next load and still finds its modules.
+    let registry = ExampleRegistry::load(&workspace).expect("manifests load");
+    let mut calc = registry.examples()[0].clone();
+    assert!(calc.is_modified());
+    assert_eq!(calc.run().expect("copy runs").as_int(), Ok(40));
+
+    calc.reset_to_original().expect("reset");
+    assert!(!calc.is_modified());
+    assert_eq!(calc.script_path, original);
+    assert!(!user.root().join("examples/calc.rhai").exists());
+    assert_eq!(calc.run().expect("original runs").as_int(), Ok(2));
+}
+
+#[test]
+fn edited_module_copies_shadow_bundled_modules() {
+    let bundled = temp_workspace(
+        "modules-bundled",
+        &[
+            (
+                "examples/manifest.toml",
+                "[[examples]]\nid = \"calc\"\nname = \"Calc\"\nscript = \"calc.rhai\"\ndoc = \"calc.html\"\ncapabilities = [\"fs.read\"]\n",
+            ),
+            (
+                "examples/calc.rhai",
+                "import \"lib\" as lib; lib::double(read_file(\"n.txt\").parse_int())",
+            ),
+            ("examples/lib.rhai", "fn double(x) { x * 2 }"),
+            ("examples/n.txt", "1"),
+            ("examples/calc.md", "Calc."),
+            ("examples/calc.html", "<p>Calc.</p>"),
+        ],
+    );
+    let user = temp_workspace(
+        "modules-user",
+        &[("examples/lib.rhai", "fn double(x) { x * 3 }")],
+    );
+    let workspace = Workspace::new(bundled.root()).with_user_root(user.root());
+    let registry = ExampleRegistry::load(&workspace).expect("manifests load");
+    let calc = &registry.examples()[0];
+
+    assert_eq!(
+        calc.resolve_file("lib.rhai"),
+        Some(user.root().join("examples/lib.rhai"))
+    );
+    assert_eq!(
+        calc.resolve_file("n.txt"),
+        Some(bundled.root().join("examples/n.txt"))
+    );
+    assert_eq!(
+        workspace.user_copy_path(&bundled.root().join("examples/n.txt")),
+        Some(user.root().join("examples/n.txt"))
+    );
+    assert_eq!(
+        calc.run().expect("runs with the edited module").as_int(),
+        Ok(3)
+    );
+}
+
+#[test]
+fn examples_meet_manifest_expectations() {
+    let registry = bundled_examples();
+    let failures: Vec<String> = registry
+        .iter()
+        .filter_map(|ex| {
+            ex.verify()
+                .err()
+                .map(|mismatches| format!("{}: {}", ex.id, mismatches.join("; ")))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+
+    // Output is also written to the example's log file.
+    let unit_tests = registry.iter().find(|e| e.id == "unit-tests").unwrap();
+    let log = std::fs::read_to_string(&unit_tests.log_path).expect("log file");
+    assert_eq!(log, "DEBUG: \"starting tests\"\nDEBUG: \"math ok\"\nx=2\n");
+}
+
+#[test]
+fn random_example_rolls_die() {
+    let registry = bundled_examples();
+    let ex = registry
+        .iter()
+        .find(|e| e.id == "random")
+        .expect("random example");
+    let script = std::fs::read_to_string(&ex.script_path).expect("script");
+    // Enough rolls to see that every one stays on the die.
+    for _ in 0..20 {
+        let result = ex.run_script(&script);
+        assert!(result.error.is_none(), "{:?}", result.error);
+        let roll = result.value.as_int().expect("integer roll");
+        assert!((1..=6).contains(&roll), "rolled {roll}");
+        assert_eq!(result.stdout, format!("{roll}\n"));
+    }
+}
+
+#[test]
+fn expectations_report_mismatches() {
+    let registry = bundled_examples();
+    let mut ex = registry
+        .iter()
+        .find(|e| e.id == "error-handling")
+        .expect("error-handling example")
+        .clone();
+
+    ex.expectations = Expectations {
+        value: Some(toml::Value::Integer(1)),
+        stdout: Some(OutputExpectation::Regex("^Caught: .+".to_string())),
+        error: None,
+    };
+    let mismatches = ex.verify().expect_err("value should not match");
+    assert_eq!(mismatches.len(), 1, "{mismatches:?}");
+
+    let result = ex.run_script(r#"throw "division by zero""#);
+    ex.expectations = Expectations {
+        error: Some("division by zero".to_string()),
+        ..Expectations::default()
+    };
+    assert_eq!(ex.expectations.check(&result), Ok(()));
+}
+
+#[test]
+fn host_engine_exposes_point_and_captures_output() {
+    let host = HostEngine::builder().build();
+    let value = host
+        .engine()
+        .eval::<f64>("let p = Point(3, 4); print(p.length()); p.length()")
+        .expect("script run");
+    assert_eq!(value, 5.0);
+    assert_eq!(host.output(), "5.0\n");
+}
+
+#[test]
+fn host_function_names_list_the_registered_api() {
+    let names = host_function_names();
+    for name in ["Point", "assert_eq", "http_get", "read_file"] {
+        assert!(
+            names.iter().any(|n| n == name),
+            "{name} missing from {names:?}"
+        );
+    }
+    assert!(!names.iter().any(|n| n == "print"));
+}
+
+#[test]
+fn engine_metadata_describes_host_builtin_and_script_functions() {
+    let host = HostEngine::builder().build();
+    let ast = host
+        .engine()
+        .compile("fn twice(x) { x * 2 }")
+        .expect("compiles");
+    let functions = host.functions(Some(&ast));
+    let find = |name: &str| {
+        functions
+            .iter()
+            .find(|f| f.name == name)
+            .unwrap_or_else(|| panic!("{name} missing"))
+    };
+
+    let rand_int = find("rand_int");
+    assert_eq!(rand_int.origin, FnOrigin::Host);
+    assert_eq!(rand_int.signature, "rand_int(min: i64, max: i64) -> i64");
+    assert_eq!(find("twice").origin, FnOrigin::Script);
+    assert_eq!(find("twice").param_list(), "x");
+    let len = find("len");
+    assert_eq!(len.origin, FnOrigin::Builtin);
+    assert!(!len.doc().is_empty());
+}
+
+#[test]
+fn host_api_and_imported_modules_are_documented() {
+    let host = HostEngine::builder().build();
+    let types = host.types();
+    assert_eq!(types.len(), 1);
+    assert_eq!(types[0].name, "Point");
+    assert!(!types[0].doc().is_empty());
+    let undocumented: Vec<String> = host
+        .functions(None)
+        .into_iter()
+        .filter(|f| f.origin == FnOrigin::Host && f.doc().is_empty())
+        .map(|f| f.signature)
+        .collect();
+    assert!(undocumented.is_empty(), "{undocumented:?}");
+
+    let registry = bundled_examples();
+    let ex = registry
+        .iter()
+        .find(|e| e.id == "custom-module")
+        .expect("custom-module example");
+    let script = std::fs::read_to_string(&ex.script_path).expect("read script");
+    let modules = ex.module_functions(&script);
+    assert_eq!(modules.len(), 1);
+    let (import, functions) = &modules[0];
+    assert_eq!(import.alias.as_deref(), Some("math"));
+    let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["square", "test_square"]);
+}
+
+#[test]
+fn runaway_loop_hits_operation_limit() {
+    let registry = bundled_examples();
+    let mut ex = registry
+        .iter()
+        .find(|e| e.id == "perf-loop")
+        .expect("perf-loop example")
+        .clone();
+    assert!(ex.limits.max_operations.is_some());
+    ex.limits.timeout_ms = None;
+    let result = ex.run_script("loop {}");
+    let err = result.error.expect("limit error");
+    assert_eq!(err.kind, ErrorKind::Limit(LimitKind::Operations));
+    assert_eq!(err.example_id, "perf-loop");
+}
+
+#[test]
+fn runaway_loop_hits_timeout() {
+    let registry = bundled_examples();
+    let mut ex = registry
+        .iter()
+        .find(|e| e.id == "hello")
+        .expect("hello example")
+        .clone();
+    ex.limits = Limits {
+        timeout_ms: Some(50),
+        ..Limits::default()
+    };
+    let result = ex.run_script("loop {}");
+    let err = result.error.expect("limit error");
+    assert_eq!(err.kind, ErrorKind::Limit(LimitKind::Timeout));
+}
+
+#[test]
+fn timeout_starts_with_each_evaluation() {
+    let host = HostEngine::builder()
+        .limits(Limits {
+            timeout_ms: Some(50),
+            ..Limits::default()
+        })
+        .build();
+    let ast = host
+        .engine()
+        .compile("let x = 0; for i in 0..10 { x += i } x")
+        .expect("compile");
+    // Time spent before each evaluation, like compiling or earlier tests,
+    // does not count against the timeout.
+    for _ in 0..2 {
+        std::thread::sleep(std::time::Duration::from_millis(80));
+        assert_eq!(host.engine().eval_ast::<i64>(&ast).expect("eval"), 45);
+    }
+    // Evaluating on the engine directly is timed out too.
+    let err = host.engine().eval::<()>("loop {}").expect_err("times out");
+    assert_eq!(LimitKind::from_error(&err), Some(LimitKind::Timeout));
+}
+
+#[test]
+fn sleep_ms_stops_on_timeout_and_cancel() {
+    let limits = Limits {
+        timeout_ms: Some(50),
+        ..Limits::default()
+    };
+    let cancel = Arc::new(AtomicBool::new(false));
+    let host = HostEngine::builder()
+        .limits(limits)
+        .capabilities([Capability::Time])
+        .cancel_flag(cancel.clone())
+        .build();
+    let started = Instant::now();
+    let err = host
+        .engine()
+        .eval::<()>("sleep_ms(60_000)")
+        .expect_err("times out");
+    assert_eq!(LimitKind::from_error(&err), Some(LimitKind::Timeout));
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    cancel.store(true, Ordering::Relaxed);
+    let err = host
+        .engine()
+        .eval::<()>("sleep_ms(60_000)")
+        .expect_err("cancelled");
+    assert!(is_cancelled(&err), "{err}");
+
+    // A negative duration does not sleep.
+    cancel.store(false, Ordering::Relaxed);
+    host.engine().eval::<()>("sleep_ms(-1)").expect("no sleep");
+}
+
+#[test]
+fn ungranted_capability_fails_cleanly() {
+    let registry = bundled_examples();
     let ex = registry
         .iter()
         .find(|e| e.id == "hello")
         .expect("hello example");
-    let value = ex.run().expect("script run");
-    assert_eq!(value.clone_cast::<String>(), "hello from rhai");
+    assert!(ex.capabilities.is_empty());
+    let result = ex.run_script(r#"read_file("examples/hot_message.txt")"#);
+    let err = result.error.expect("read_file should be rejected");
+    assert_eq!(err.kind, ErrorKind::Host);
+    assert_eq!(
+        err.message,
+        "capability not granted: `read_file` requires `fs.read`"
+    );
+
+    // Scripts can catch it and see which capability is missing.
+    let script = r#"let missing = (); try { read_file("x") } catch (err) { missing = err.capability } missing"#;
+    let result = ex.run_script(script);
+    assert_eq!(result.value.into_string().ok().as_deref(), Some("fs.read"));
 }
 
 #[test]
-fn unit_test_example_logs() {
-    let registry = ExampleRegistry::all();
+fn spawned_run_can_be_cancelled() {
+    let registry = bundled_examples();
     let ex = registry
         .iter()
-        .find(|e| e.id == "unit-tests")
-        .expect("unit-tests example");
-    let value = ex.run().expect("script run");
-    assert!(value.as_bool().unwrap());
-    let expected = "DEBUG: \"starting tests\"\nDEBUG: \"math ok\"\nx=2\n";
-    let log = std::fs::read_to_string("logs/unit-tests.log").expect("log file");
-    assert_eq!(log, expected);
+        .find(|e| e.id == "hello")
+        .expect("hello example");
+    let handle = ex.spawn_script("loop {}".to_string());
+    handle.cancel();
+    let result = loop {
+        if let Some(result) = handle.try_result() {
+            break result;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+    assert_eq!(result.error.map(|e| e.kind), Some(ErrorKind::Cancelled));
 }
 
 #[test]
-fn custom_module_example_runs() {
-    let registry = ExampleRegistry::all();
+fn spawned_run_streams_output_lines() {
+    let registry = bundled_examples();
     let ex = registry
         .iter()
-        .find(|e| e.id == "custom-module")
-        .expect("custom-module example");
-    let value = ex.run().expect("script run");
-    let log = std::fs::read_to_string("logs/custom-module.log").expect("log file");
-    assert!(log.contains("square(4) = 16"));
-    assert_eq!(value.clone_cast::<i64>(), 16);
+        .find(|e| e.id == "hello")
+        .expect("hello example");
+    let handle = ex.spawn_script(r#"debug("starting"); print("x=2"); true"#.to_string());
+    let lines: Vec<String> = handle.output_lines().collect();
+    assert_eq!(lines, ["DEBUG: \"starting\"", "x=2"]);
+    let result = handle.wait();
+    assert!(result.error.is_none());
 }
 
 #[test]
-fn async_sim_example_runs() {
-    let registry = ExampleRegistry::all();
+fn debugger_pauses_in_modules_and_steps() {
+    let registry = bundled_examples();
     let ex = registry
         .iter()
-        .find(|e| e.id == "async-sim")
-        .expect("async-sim example");
-    let value = ex.run().expect("script run");
-    let log = std::fs::read_to_string("logs/async-sim.log").expect("log file");
-    assert!(log.contains("task complete"));
-    assert_eq!(value.clone_cast::<String>(), "done");
+        .find(|e| e.id == "custom-module")
+        .expect("custom-module example");
+    let script = std::fs::read_to_string(&ex.script_path).expect("read script");
+    let module = ex.source_path("math_utils.rhai");
+    assert!(module.as_ref().is_some_and(|p| p.is_file()), "{module:?}");
+    let breakpoint = Breakpoint {
+        path: module,
+        line: 1,
+    };
+    let mut session = ex.debug_script(script.clone(), [breakpoint], false);
+
+    let pause = session
+        .wait_for_pause()
+        .expect("paused in the module")
+        .clone();
+    assert_eq!(pause.source.as_deref(), Some("math_utils.rhai"));
+    assert_eq!(pause.line, 1);
+    let frames: Vec<_> = pause
+        .call_stack
+        .iter()
+        .map(|f| (f.function.as_str(), f.source.as_deref(), f.line))
+        .collect();
+    assert_eq!(frames, [("square", None, Some(4))]);
+    let locals: Vec<_> = pause
+        .variables
+        .iter()
+        .map(|v| (v.name.as_str(), v.value.as_str()))
+        .collect();
+    assert_eq!(locals, [("x", "4")]);
+
+    session.resume(DebugCommand::StepOut);
+    let pause = session
+        .wait_for_pause()
+        .expect("back in the script")
+        .clone();
+    assert_eq!((pause.source, pause.line), (None, 5));
+    assert!(pause.call_stack.is_empty());
+    session.resume(DebugCommand::StepOver);
+    assert_eq!(session.wait_for_pause().map(|p| p.line), Some(6));
+    session.resume(DebugCommand::Continue);
+    let result = session.wait();
+    assert!(result.error.is_none(), "{:?}", result.error);
+    assert_eq!(result.value.as_int().ok(), Some(16));
+
+    let mut session = ex.debug_script(script, [], true);
+    assert_eq!(session.wait_for_pause().map(|p| p.line), Some(1));
+    session.resume(DebugCommand::StepOver);
+    assert_eq!(session.wait_for_pause().map(|p| p.line), Some(3));
+    session.stop();
+    let result = session.wait();
+    assert_eq!(result.error.map(|e| e.kind), Some(ErrorKind::Cancelled));
+
+    // A breakpoint in a loop body pauses on every iteration.
+    let in_loop = Breakpoint {
+        path: None,
+        line: 3,
+    };
+    let script = "let x = 0;\nfor i in 0..3 {\n    x += i;\n}\nx";
+    let mut session = ex.debug_script(script.to_string(), [in_loop], false);
+    let mut pauses = 0;
+    while session.wait_for_pause().is_some() {
+        pauses += 1;
+        session.resume(DebugCommand::Continue);
+    }
+    assert_eq!(pauses, 3);
 }
 
 #[test]
-fn collections_example_runs() {
-    let registry = ExampleRegistry::all();
+fn runs_keep_their_final_scope() {
+    let registry = bundled_examples();
     let ex = registry
         .iter()
         .find(|e| e.id == "collections")
         .expect("collections example");
-    let value = ex.run().expect("script run");
-    assert_eq!(value.clone_cast::<i64>(), 12);
+    let script = std::fs::read_to_string(&ex.script_path).expect("read script");
+    let result = ex.run_script(&script);
+    assert!(result.error.is_none(), "{:?}", result.error);
+    let variables = result.variables();
+    let names: Vec<&str> = variables.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, ["numbers", "total", "scores"]);
+
+    let numbers = &variables[0];
+    assert_eq!(
+        (numbers.type_name.as_str(), numbers.value.as_str()),
+        ("array", "4 items")
+    );
+    let items: Vec<_> = numbers
+        .children
+        .iter()
+        .map(|v| (v.name.as_str(), v.type_name.as_str(), v.value.as_str()))
+        .collect();
+    assert_eq!(
+        items,
+        [
+            ("[0]", "i64", "2"),
+            ("[1]", "i64", "4"),
+            ("[2]", "i64", "6"),
+            ("[3]", "i64", "3")
+        ]
+    );
+    let scores: Vec<_> = variables[2]
+        .children
+        .iter()
+        .map(|v| (v.name.as_str(), v.value.as_str()))
+        .collect();
+    assert_eq!(scores, [("Alice", "2"), ("Bob", "3"), ("Cara", "12")]);
+
+    let result = ex.run_script(r#"const greeting = "hi"; let p = Point(1, 2);"#);
+    let variables = result.variables();
+    let described: Vec<_> = variables
+        .iter()
+        .map(|v| (v.name.as_str(), v.type_name.as_str(), v.constant))
+        .collect();
+    assert_eq!(
+        described,
+        [("greeting", "string", true), ("p", "Point", false)]
+    );
+    assert_eq!(variables[0].value, "\"hi\"");
 }
 
 #[test]
-fn error_handling_example_catches() {
-    let registry = ExampleRegistry::all();
+fn check_script_compiles_without_running() {
+    let registry = bundled_examples();
     let ex = registry
         .iter()
-        .find(|e| e.id == "error-handling")
-        .expect("error-handling example");
-    let value = ex.run().expect("script run");
-    let map = value.clone_cast::<rhai::Map>();
-    assert_eq!(map["msg"].clone_cast::<String>(), "division by zero");
-    assert_eq!(map["value"].clone_cast::<i64>(), -1);
+        .find(|e| e.id == "hello")
+        .expect("hello example");
+
+    assert!(ex.check_script("throw \"not run\";").is_ok());
+    let err = ex
+        .check_script("let x = 1;\nlet y = ;")
+        .expect_err("parse error");
+    assert_eq!(err.kind, ErrorKind::Parse);
+    assert_eq!((err.line, err.column), (Some(2), Some(9)));
 }
 
 #[test]
-fn random_example_rolls_die() {
-    let registry = ExampleRegistry::all();
+fn syntax_tree_lists_statements_and_functions_in_source_order() {
+    let host = HostEngine::builder().build();
+    let ast = host
+        .engine()
+        .compile("let total = 0;\nfn add(a, b) {\n    a + b\n}\nprint(add(total, 2));")
+        .expect("compiles");
+    let tree = syntax_tree(&ast);
+    let describe = |n: &AstNode| (n.kind, n.label.clone(), n.line, n.column);
+
+    let top: Vec<_> = tree.iter().map(describe).collect();
+    assert_eq!(
+        top,
+        [
+            (NodeKind::Let, Some("total".into()), Some(1), Some(1)),
+            (NodeKind::Fn, Some("add(a, b)".into()), Some(2), Some(14)),
+            (NodeKind::FnCall, Some("print".into()), Some(5), Some(1)),
+        ]
+    );
+    let sum = &tree[1].children[0];
+    assert_eq!(
+        describe(sum),
+        (NodeKind::Operator, Some("+".into()), Some(3), Some(7))
+    );
+    let operands: Vec<_> = sum.children.iter().map(|n| n.label.clone()).collect();
+    assert_eq!(operands, [Some("a".into()), Some("b".into())]);
+    let call = &tree[2].children[0];
+    assert_eq!(call.label.as_deref(), Some("add"));
+    assert_eq!(call.children.len(), 2);
+}
+
+#[test]
+fn ast_exports_as_versioned_json() {
+    let registry = bundled_examples();
     let ex = registry
         .iter()
-        .find(|e| e.id == "random")
-        .expect("random example");
-    let value = ex.run().expect("script run");
-    let log = std::fs::read_to_string("logs/random.log").expect("log file");
-    let roll: i64 = log.trim().parse().expect("number");
-    assert!(roll >= 1 && roll <= 6);
-    assert_eq!(roll, value.clone_cast::<i64>());
+        .find(|e| e.id == "custom-module")
+        .expect("custom-module example");
+    let script = "import \"math_utils.rhai\" as math;\n/// Twice `x`.\nprivate fn twice(x) { x * 2.5 }\nlet n = math::square(3);\ntwice(n)";
+    let result = ex.run_script(script);
+    assert!(result.error.is_none(), "{:?}", result.error);
+
+    let json: serde_json::Value = serde_json::from_str(&result.ast_json()).expect("valid JSON");
+    assert_eq!(json["version"], AST_JSON_VERSION);
+    assert_eq!(
+        json["imports"],
+        serde_json::json!([{ "path": "math_utils.rhai", "alias": "math", "line": 1, "column": 1 }])
+    );
+    let twice = &json["functions"][0];
+    assert_eq!(twice["name"], "twice");
+    assert_eq!(twice["params"], serde_json::json!(["x"]));
+    assert_eq!(twice["private"], true);
+    assert_eq!(twice["doc_comments"], serde_json::json!(["/// Twice `x`."]));
+    let product = &twice["body"][0];
+    assert_eq!(product["kind"], "Operator");
+    assert_eq!(
+        product["children"][1]["value"],
+        serde_json::json!({ "type": "float", "value": 2.5 })
+    );
+
+    let statements = json["statements"].as_array().expect("statements");
+    let kinds: Vec<&str> = statements
+        .iter()
+        .map(|s| s["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, ["Import", "Let", "FnCall"]);
+    let call = &statements[1]["children"][0];
+    assert_eq!(call["label"], "math::square");
+    assert_eq!((&call["line"], &call["column"]), (&4.into(), &9.into()));
+    let argument = &call["children"][0];
+    assert_eq!(argument["kind"], "Integer");
+    assert_eq!(argument["label"], serde_json::Value::Null);
+    assert_eq!(
+        argument["value"],
+        serde_json::json!({ "type": "int", "value": 3 })
+    );
+}
+
+#[test]
+fn optimization_levels_fold_constants_but_agree_on_the_result() {
+    let registry = bundled_examples();
+    let ex = registry
+        .iter()
+        .find(|e| e.id == "hello")
+        .expect("hello example");
+
+    let runs =
+        ex.compare_optimizations("const X = 2;\nlet y = X * 3 + 1;\nif false { print(y) }\ny");
+    let levels: Vec<_> = runs.iter().map(|r| r.level).collect();
+    assert_eq!(levels, OPTIMIZATION_LEVELS);
+    for run in &runs {
+        assert!(run.result.error.is_none(), "{:?}", run.result.error);
+        assert_eq!(run.result.value.as_int().ok(), Some(7));
+    }
+    assert!(runs[0].outline.contains("Operator *"));
+    assert!(runs[0].outline.contains("If"));
+    for run in &runs[1..] {
+        assert!(
+            run.outline.contains("Let y\n  Integer 7\n"),
+            "{}",
+            run.outline
+        );
+        assert!(!run.outline.contains("If"));
+    }
+}
+
+#[test]
+fn errors_report_kind_position_and_call_stack() {
+    let registry = bundled_examples();
+    let ex = registry
+        .iter()
+        .find(|e| e.id == "hello")
+        .expect("hello example");
+
+    let err = ex.run_script("let x = ;").error.expect("parse error");
+    assert_eq!(err.kind, ErrorKind::Parse);
+    assert_eq!(err.line, Some(1));
+
+    let script = "fn fail(x) {\n    throw `bad ${x}`;\n}\nfail(1)";
+    let err = ex.run_script(script).error.expect("runtime error");
+    assert_eq!(err.kind, ErrorKind::Runtime);
+    assert_eq!(err.message, "bad 1");
+    assert_eq!(err.line, Some(2));
+    assert_eq!(err.call_stack.len(), 1);
+    assert_eq!(err.call_stack[0].function, "fail");
+    assert_eq!(err.call_stack[0].line, Some(4));
+}
+
+#[test]
+fn failed_assertions_are_runtime_errors() {
+    let registry = bundled_examples();
+    let ex = registry
+        .iter()
+        .find(|e| e.id == "hello")
+        .expect("hello example");
+
+    let err = ex
+        .run_script("let x = 1 + 1;\nassert_eq(x, 3);")
+        .error
+        .expect("assertion error");
+    assert_eq!(err.kind, ErrorKind::Runtime);
+    assert_eq!(err.message, "assert_eq failed: expected 3, actual 2");
+    assert_eq!(err.line, Some(2));
+
+    let err = ex
+        .run_script(r#"assert(false, "math is broken")"#)
+        .error
+        .expect("assertion error");
+    assert_eq!(err.message, "math is broken: assertion failed");
+
+    // Every helper takes an optional message as its last argument.
+    for (call, detail) in [
+        ("assert_true(false, \"msg\")", "expected true, got false"),
+        ("assert_false(true, \"msg\")", "expected false, got true"),
+        (
+            "assert_eq(1, 2, \"msg\")",
+            "assert_eq failed: expected 2, actual 1",
+        ),
+        (
+            "assert_ne(1, 1, \"msg\")",
+            "assert_ne failed: expected anything but 1, actual 1",
+        ),
+        (
+            "assert_throws(|| 5, \"msg\")",
+            "assert_throws failed: function returned 5",
+        ),
+    ] {
+        let err = ex.run_script(call).error.expect("assertion error");
+        assert_eq!(err.message, format!("msg: {detail}"), "{call}");
+    }
+
+    let result = ex.run_script(
+        r#"
+        assert_ne("a", "b");
+        assert_true(1 < 2);
+        assert_false(1 > 2);
+        let thrown = assert_throws(|| throw "boom", "should throw");
+        try { assert(false); } catch (e) { thrown += "!"; }
+        thrown
+        "#,
+    );
+    assert!(result.error.is_none(), "{:?}", result.error);
+    assert_eq!(result.value.clone_cast::<String>(), "boom!");
+}
+
+#[test]
+fn test_runner_discovers_script_and_module_tests() {
+    let registry = bundled_examples();
+    let ex = registry
+        .iter()
+        .find(|e| e.id == "custom-module")
+        .expect("custom-module example");
+    let script = r#"
+        import "math_utils.rhai" as math;
+
+        fn test_uses_module() { assert_eq(math::square(4), 16); }
+        fn test_fails() { assert_eq(math::square(2), 5); }
+        fn test_errors() { undefined_fn(); }
+        fn helper(x) { x }
+
+        print("top-level code must not run");
+    "#;
+    let report = ex.run_tests(script);
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    let outcome = |name: &str| {
+        report
+            .cases
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.outcome.clone())
+            .expect(name)
+    };
+    assert_eq!(outcome("test_uses_module"), TestOutcome::Passed);
+    assert!(matches!(outcome("test_fails"), TestOutcome::Failed(_)));
+    assert!(matches!(outcome("test_errors"), TestOutcome::Error(_)));
+    let module_case = report
+        .cases
+        .iter()
+        .find(|c| c.name == "test_square")
+        .expect("module test");
+    assert_eq!(module_case.module.as_deref(), Some("math_utils.rhai"));
+    assert_eq!(module_case.outcome, TestOutcome::Passed);
+    assert_eq!(report.cases.len(), 4);
+    assert_eq!(report.passed(), 2);
+    assert!(report.cases.iter().all(|c| c.stdout.is_empty()));
+}
+
+#[test]
+fn unit_tests_example_functions_pass() {
+    let registry = bundled_examples();
+    let ex = registry
+        .iter()
+        .find(|e| e.id == "unit-tests")
+        .expect("unit-tests example");
+    let script = std::fs::read_to_string(&ex.script_path).expect("script source");
+    let report = ex.run_tests(&script);
+    assert!(report.is_success(), "{report:?}");
+    assert_eq!(report.cases.len(), 2);
 }